@@ -0,0 +1,446 @@
+//! `cargo you-can [CHECK ARGS...]`
+//!
+//! Finds the references that really need the borrow checker turned off in the
+//! code that [`#[you_can::turn_off_the_borrow_checker]`][OFF] is applied to.
+//!
+//! The macro suppresses every reference it can, including the ones that were
+//! fine. This copies the workspace to a temporary directory, gets the
+//! location of each reference it suppresses from its reports, and then checks
+//! the code again with each of them marked with
+//! `#[you_can::keep_the_borrow_checker]` in turn. If the code still compiles,
+//! the reference stays marked, and if it doesn't, the reference must be
+//! suppressed, and we print the errors the borrow checker gave for it. Any
+//! arguments are passed along to `cargo check`.
+//!
+//! Path dependencies outside of the workspace must use absolute paths, since
+//! the workspace is moved.
+//!
+//! [OFF]: https://docs.rs/you-can/latest/you_can/attr.turn_off_the_borrow_checker.html
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::{exit, Command},
+};
+
+/// A reference that the macro suppressed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Reference {
+    /// The path of the file it's in, relative to the workspace.
+    file: PathBuf,
+    /// The line it starts on, counting from 1.
+    line: usize,
+    /// The column it starts at, counting characters from 1.
+    column: usize,
+    /// `&`, `&mut`, `ref`, or `ref mut`.
+    kind: String,
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let Reference {
+            file,
+            line,
+            column,
+            kind,
+        } = self;
+        write!(f, "{}:{line}:{column} (`{kind}`)", file.display())
+    }
+}
+
+/// What we found out about the references that the macro suppressed.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Findings {
+    /// The references that don't compile with the borrow checker, and the
+    /// errors it gave for them.
+    needed: Vec<(Reference, Vec<String>)>,
+    /// The references that compile with the borrow checker.
+    kept: Vec<Reference>,
+    /// The references we couldn't mark with `#[keep_the_borrow_checker]`.
+    unmarkable: Vec<Reference>,
+}
+
+impl std::fmt::Display for Findings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let Findings {
+            needed,
+            kept,
+            unmarkable,
+        } = self;
+        let total = needed.len() + kept.len() + unmarkable.len();
+        if total == 0 {
+            return writeln!(f, "The borrow checker isn't turned off for any references.");
+        }
+        for (reference, errors) in needed {
+            writeln!(f, "{reference} needs the borrow checker turned off:")?;
+            for error in errors {
+                write!(f, "{error}")?;
+            }
+            writeln!(f)?;
+        }
+        for reference in kept {
+            writeln!(f, "{reference} doesn't need the borrow checker turned off")?;
+        }
+        for reference in unmarkable {
+            writeln!(
+                f,
+                "{reference} couldn't be checked, because it can't be marked with \
+                 #[keep_the_borrow_checker]"
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{} of {total} suppressed references need the borrow checker turned off, and {} \
+             couldn't be checked.",
+            needed.len(),
+            unmarkable.len()
+        )
+    }
+}
+
+fn main() {
+    let args = cargo_args(std::env::args());
+    let outcome = run(&args);
+    match &outcome {
+        Ok(findings) => print!("{findings}"),
+        Err(errors) => {
+            for error in errors {
+                eprint!("{error}");
+            }
+            eprintln!("error: the code doesn't compile even with the borrow checker turned off");
+        },
+    }
+    exit(exit_status(&outcome));
+}
+
+/// Returns the arguments to pass along to `cargo check`, given the arguments
+/// we were run with. Cargo runs us as `cargo-you-can you-can ARGS...`.
+fn cargo_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut args = args.into_iter().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "you-can") {
+        args.next();
+    }
+    args.collect()
+}
+
+/// The status we exit with: we fail if the code doesn't compile at all, but
+/// not if it needs the borrow checker turned off.
+fn exit_status(outcome: &Result<Findings, Vec<String>>) -> i32 {
+    match outcome {
+        Ok(_) => 0,
+        Err(_) => 1,
+    }
+}
+
+/// Checks each of the references that the macro suppresses in the current
+/// workspace, or returns the errors if it doesn't compile at all.
+fn run(args: &[String]) -> Result<Findings, Vec<String>> {
+    let workspace = workspace_root();
+    let scratch = std::env::temp_dir().join(format!("cargo-you-can-{}", std::process::id()));
+    let copy = scratch.join("workspace");
+    let reports = scratch.join("reports");
+    copy_dir(&workspace, &copy);
+
+    eprintln!(
+        "Checking {} with the borrow checker turned off...",
+        workspace.display()
+    );
+    let errors = check(&copy, &scratch, args, Some(&reports));
+    if !errors.is_empty() {
+        let _ = std::fs::remove_dir_all(&scratch);
+        return Err(errors);
+    }
+
+    let mut references = read_reports(&reports, &copy);
+    references.sort();
+    references.dedup();
+
+    let originals: BTreeMap<PathBuf, String> = references
+        .iter()
+        .map(|reference| {
+            let source = std::fs::read_to_string(copy.join(&reference.file)).unwrap();
+            (reference.file.clone(), source)
+        })
+        .collect();
+
+    let mut findings = Findings::default();
+    for (index, reference) in references.iter().enumerate() {
+        eprintln!(
+            "[{}/{}] Checking {reference}...",
+            index + 1,
+            references.len()
+        );
+        if !markable(reference, &originals) {
+            findings.unmarkable.push(reference.clone());
+            continue;
+        }
+
+        let mut marked = findings.kept.clone();
+        marked.push(reference.clone());
+        write_markers(&copy, &originals, &marked);
+        let errors = check(&copy, &scratch, args, None);
+        if errors.is_empty() {
+            findings.kept.push(reference.clone());
+        } else {
+            findings.needed.push((reference.clone(), errors));
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&scratch);
+    Ok(findings)
+}
+
+/// Finds the root of the workspace we're in.
+fn workspace_root() -> PathBuf {
+    let output = Command::new(cargo())
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .output()
+        .unwrap();
+    if !output.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        exit(1);
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    PathBuf::from(metadata["workspace_root"].as_str().unwrap())
+}
+
+/// Copies a directory, except for build output and version control.
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let name = entry.file_name();
+        if name == "target" || name == ".git" {
+            continue;
+        }
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &to.join(&name));
+        } else {
+            std::fs::copy(entry.path(), to.join(&name)).unwrap();
+        }
+    }
+}
+
+/// Runs `cargo check` in a workspace, returning the rendered errors, and
+/// writing reports of the suppressed references to `reports` if given.
+fn check(workspace: &Path, scratch: &Path, args: &[String], reports: Option<&Path>) -> Vec<String> {
+    let mut command = Command::new(cargo());
+    command
+        .current_dir(workspace)
+        .env("CARGO_TARGET_DIR", scratch.join("target"))
+        .env_remove("YOU_CAN_KEEP_THE_BORROW_CHECKER")
+        .args(["check", "--message-format=json"])
+        .args(args);
+    match reports {
+        Some(reports) => command.env("YOU_CAN_REPORT_DIR", reports),
+        None => command.env_remove("YOU_CAN_REPORT_DIR"),
+    };
+    let output = command.output().unwrap();
+    compiler_errors(&output.stdout, &output.stderr, output.status.success())
+}
+
+/// Returns the rendered errors from the JSON messages `cargo check` printed,
+/// or everything it printed to stderr if it failed without any.
+fn compiler_errors(stdout: &[u8], stderr: &[u8], success: bool) -> Vec<String> {
+    let mut errors = vec![];
+    for line in stdout.split(|&byte| byte == b'\n') {
+        let message: serde_json::Value = match serde_json::from_slice(line) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+        if message["reason"] == "compiler-message" && message["message"]["level"] == "error" {
+            if let Some(rendered) = message["message"]["rendered"].as_str() {
+                // Skip the "aborting due to previous errors" summary.
+                if message["message"]["spans"]
+                    .as_array()
+                    .is_some_and(|spans| !spans.is_empty())
+                {
+                    errors.push(rendered.to_string());
+                }
+            }
+        }
+    }
+    if errors.is_empty() && !success {
+        errors.push(String::from_utf8_lossy(stderr).into_owned());
+    }
+    errors
+}
+
+/// Reads the references from the reports written by the macro.
+fn read_reports(reports: &Path, workspace: &Path) -> Vec<Reference> {
+    let mut references = vec![];
+    let entries = match std::fs::read_dir(reports) {
+        Ok(entries) => entries,
+        Err(_) => return references,
+    };
+    for entry in entries {
+        let report: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap();
+        for reference in report["references"].as_array().unwrap() {
+            let file = PathBuf::from(reference["file"].as_str().unwrap());
+            let file = file.strip_prefix(workspace).unwrap_or(&file).to_owned();
+            // We can't change files outside of the workspace, like those of
+            // dependencies.
+            if file.is_absolute() || !workspace.join(&file).exists() {
+                continue;
+            }
+            references.push(Reference {
+                file,
+                line: reference["start"]["line"].as_u64().unwrap() as usize,
+                column: reference["start"]["column"].as_u64().unwrap() as usize,
+                kind: reference["kind"].as_str().unwrap().to_string(),
+            });
+        }
+    }
+    references
+}
+
+/// Whether we can mark a reference with `#[keep_the_borrow_checker]`. We
+/// can't put attributes in patterns, or in format strings.
+fn markable(reference: &Reference, originals: &BTreeMap<PathBuf, String>) -> bool {
+    if reference.kind != "&" && reference.kind != "&mut" {
+        return false;
+    }
+    let line = originals[&reference.file].lines().nth(reference.line - 1);
+    let next = line.and_then(|line| line.chars().nth(reference.column - 1));
+    next.is_some_and(|next| next == '&' || next == '(' || next == '_' || next.is_alphabetic())
+}
+
+/// Restores the original sources, and then marks the given references with
+/// `#[keep_the_borrow_checker]`.
+fn write_markers(workspace: &Path, originals: &BTreeMap<PathBuf, String>, marked: &[Reference]) {
+    for (file, original) in originals {
+        let mut lines: Vec<String> = original.split('\n').map(String::from).collect();
+        let mut positions: Vec<(usize, usize)> = marked
+            .iter()
+            .filter(|reference| &reference.file == file)
+            .map(|reference| (reference.line - 1, reference.column - 1))
+            .collect();
+        // Insert from the end, so that the earlier positions don't move.
+        positions.sort();
+        for &(line, column) in positions.iter().rev() {
+            let offset = lines[line]
+                .char_indices()
+                .nth(column)
+                .map_or(lines[line].len(), |(offset, _)| offset);
+            lines[line].insert_str(offset, "#[you_can::keep_the_borrow_checker] ");
+        }
+        std::fs::write(workspace.join(file), lines.join("\n")).unwrap();
+    }
+}
+
+/// The `cargo` that's running us, or else the one on the path.
+fn cargo() -> String {
+    std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(line: usize, kind: &str) -> Reference {
+        Reference {
+            file: PathBuf::from("src/main.rs"),
+            line,
+            column: 17,
+            kind: kind.to_string(),
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn cargo_args_skip_the_subcommand() {
+        assert_eq!(cargo_args(args(&["cargo-you-can", "you-can", "--lib"])), [
+            "--lib"
+        ]);
+        assert_eq!(cargo_args(args(&["cargo-you-can", "--lib"])), ["--lib"]);
+        assert_eq!(
+            cargo_args(args(&["cargo-you-can", "you-can", "-p", "you-can"])),
+            ["-p", "you-can"]
+        );
+        assert!(cargo_args(args(&["cargo-you-can"])).is_empty());
+    }
+
+    #[test]
+    fn read_reports_from_the_report_dir() {
+        let scratch =
+            std::env::temp_dir().join(format!("cargo-you-can-test-{}", std::process::id()));
+        let workspace = scratch.join("workspace");
+        let reports = scratch.join("reports");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::create_dir_all(&reports).unwrap();
+        std::fs::write(workspace.join("src/main.rs"), "").unwrap();
+        let main = workspace.join("src/main.rs");
+        std::fs::write(
+            reports.join("main.json"),
+            format!(
+                r#"{{"crate":"main","file":"src/main.rs","line":1,"column":1,"references":[
+                    {{"file":{main:?},"start":{{"line":3,"column":17}},"end":{{"line":3,"column":30}},"kind":"&mut","item":null}},
+                    {{"file":"src/main.rs","start":{{"line":4,"column":17}},"end":{{"line":4,"column":30}},"kind":"&","item":null}},
+                    {{"file":"/elsewhere/lib.rs","start":{{"line":5,"column":1}},"end":{{"line":5,"column":2}},"kind":"&","item":null}},
+                    {{"file":"src/missing.rs","start":{{"line":6,"column":1}},"end":{{"line":6,"column":2}},"kind":"&","item":null}}
+                ]}}"#
+            ),
+        )
+        .unwrap();
+
+        let mut references = read_reports(&reports, &workspace);
+        references.sort();
+        assert_eq!(references, [reference(3, "&mut"), reference(4, "&")]);
+        assert!(read_reports(&scratch.join("missing"), &workspace).is_empty());
+
+        std::fs::remove_dir_all(&scratch).unwrap();
+    }
+
+    #[test]
+    fn compiler_errors_skip_the_summary() {
+        let stdout = br#"{"reason":"compiler-artifact"}
+{"reason":"compiler-message","message":{"level":"warning","rendered":"warning: unused\n","spans":[{}]}}
+{"reason":"compiler-message","message":{"level":"error","rendered":"error[E0499]: cannot borrow\n","spans":[{}]}}
+{"reason":"compiler-message","message":{"level":"error","rendered":"error: aborting due to 1 previous error\n","spans":[]}}
+not json"#;
+        assert_eq!(compiler_errors(stdout, b"", false), [
+            "error[E0499]: cannot borrow\n"
+        ]);
+        assert_eq!(compiler_errors(b"", b"error: no such command\n", false), [
+            "error: no such command\n"
+        ]);
+        assert!(compiler_errors(b"", b"warning: ignored\n", true).is_empty());
+    }
+
+    #[test]
+    fn exit_status_only_fails_if_the_code_does_not_compile() {
+        assert_eq!(exit_status(&Ok(Findings::default())), 0);
+        let findings = Findings {
+            needed: vec![(reference(3, "&mut"), vec!["error\n".to_string()])],
+            ..Findings::default()
+        };
+        assert_eq!(exit_status(&Ok(findings)), 0);
+        assert_eq!(exit_status(&Err(vec!["error\n".to_string()])), 1);
+    }
+
+    #[test]
+    fn findings_display() {
+        let findings = Findings {
+            needed: vec![(reference(3, "&mut"), vec!["error[E0499]\n".to_string()])],
+            kept: vec![reference(4, "&")],
+            unmarkable: vec![reference(5, "ref")],
+        };
+        assert_eq!(
+            findings.to_string(),
+            "src/main.rs:3:17 (`&mut`) needs the borrow checker turned \
+             off:\nerror[E0499]\n\nsrc/main.rs:4:17 (`&`) doesn't need the borrow checker turned \
+             off\nsrc/main.rs:5:17 (`ref`) couldn't be checked, because it can't be marked with \
+             #[keep_the_borrow_checker]\n\n1 of 3 suppressed references need the borrow checker \
+             turned off, and 1 couldn't be checked.\n"
+        );
+        assert_eq!(
+            Findings::default().to_string(),
+            "The borrow checker isn't turned off for any references.\n"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Runs `cargo you-can` on the crates in `tests/fixtures`.
+
+use std::{path::Path, process::Output};
+
+/// Copies a fixture crate to a temporary directory with a manifest that
+/// depends on this `you-can`, and runs `cargo you-can` in it.
+fn cargo_you_can(fixture: &str) -> Output {
+    let you_can = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let dir = std::env::temp_dir().join(format!(
+        "cargo-you-can-fixture-{fixture}-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{fixture}\"\nversion = \"0.0.0\"\nedition = \
+             \"2021\"\n\n[dependencies]\nyou-can = {{ path = {:?} }}\n\n[workspace]\n",
+            you_can.display().to_string()
+        ),
+    )
+    .unwrap();
+    std::fs::copy(
+        Path::new("tests/fixtures")
+            .join(fixture)
+            .join("src/main.rs"),
+        dir.join("src/main.rs"),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cargo-you-can"))
+        .arg("you-can")
+        .current_dir(&dir)
+        .env_remove("CARGO_TARGET_DIR")
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+#[test]
+fn aliasing() {
+    let output = cargo_you_can("aliasing");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0), "{stdout}");
+    assert!(stdout.starts_with("src/main.rs:4:17 (`&mut`) needs the borrow checker turned off:\n"));
+    assert!(stdout.contains("src/main.rs:5:18 (`&`) doesn't need the borrow checker turned off\n"));
+    assert!(stdout.ends_with(
+        "1 of 4 suppressed references need the borrow checker turned off, and 1 couldn't be \
+         checked.\n"
+    ));
+}
+
+#[test]
+fn broken() {
+    let output = cargo_you_can("broken");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    assert!(
+        stderr.contains("error[E0308]: mismatched types"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("the code doesn't compile even with the borrow checker turned off"));
+    assert!(output.stdout.is_empty());
+}
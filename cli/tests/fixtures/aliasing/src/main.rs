@@ -0,0 +1,9 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut owned = vec![1, 2];
+    let first = &mut owned[0];
+    let second = &owned[1];
+    println!("{second}");
+    owned.clear();
+    *first += 1;
+}
@@ -0,0 +1,6 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let owned: Vec<i32> = vec!["not a number"];
+    let first = &owned[0];
+    println!("{first}");
+}
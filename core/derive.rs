@@ -0,0 +1,112 @@
+use {
+    proc_macro2::{Span, TokenStream},
+    quote::{quote, ToTokens},
+    syn::{fold::Fold, parse_quote},
+};
+
+/// Implements `BorrowUnchecked` for a struct or enum, like
+/// `#[derive(BorrowUnchecked)]` does, unbinding all of its lifetime
+/// parameters. If the tokens aren't a struct or enum, returns a
+/// `compile_error!` instead.
+pub fn derive_borrow_unchecked(tokens: TokenStream) -> TokenStream {
+    let input: syn::DeriveInput = match syn::parse2(tokens) {
+        Ok(input) => input,
+        Err(error) => return error.to_compile_error(),
+    };
+    if let syn::Data::Union(union) = &input.data {
+        return syn::Error::new(
+            union.union_token.span,
+            "#[derive(BorrowUnchecked)] doesn't support unions",
+        )
+        .to_compile_error();
+    }
+
+    let original = fresh_lifetime(&input.generics, "__original");
+    let unbounded = fresh_lifetime(&input.generics, "__unbounded");
+    let mut unbinder = Unbinder {
+        lifetimes: input
+            .generics
+            .lifetimes()
+            .map(|param| param.lifetime.ident.clone())
+            .collect(),
+        unbounded: unbounded.clone(),
+    };
+
+    let ident = &input.ident;
+    let (_, type_generics, _) = input.generics.split_for_impl();
+    let unbounded_arguments = input.generics.params.iter().map(|param| match param {
+        syn::GenericParam::Lifetime(_) => quote! { #unbounded },
+        syn::GenericParam::Type(param) => param.ident.to_token_stream(),
+        syn::GenericParam::Const(param) => param.ident.to_token_stream(),
+    });
+
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote! { #unbounded });
+    generics.params.insert(0, parse_quote! { #original });
+    let predicates = &mut generics.make_where_clause().predicates;
+    // The unbounded type needs to meet the same bounds as the original, with
+    // its lifetimes replaced, and its type parameters have to outlive its new
+    // lifetime for it to be well-formed.
+    for param in input.generics.type_params() {
+        let param_ident = &param.ident;
+        predicates.push(parse_quote! { #param_ident: #unbounded });
+        if !param.bounds.is_empty() {
+            let bounds = param
+                .bounds
+                .iter()
+                .map(|bound| unbinder.fold_type_param_bound(bound.clone()));
+            predicates.push(parse_quote! { #param_ident: #(#bounds)+* });
+        }
+    }
+    if let Some(where_clause) = &input.generics.where_clause {
+        for predicate in &where_clause.predicates {
+            if !matches!(predicate, syn::WherePredicate::Lifetime(_)) {
+                predicates.push(unbinder.fold_where_predicate(predicate.clone()));
+            }
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        unsafe impl #impl_generics ::you_can::BorrowUnchecked<#original, #unbounded>
+            for #ident #type_generics #where_clause
+        {
+            type Unbounded = #ident<#(#unbounded_arguments),*>;
+
+            #[inline(always)]
+            unsafe fn borrow_unchecked(self) -> Self::Unbounded {
+                unsafe { ::you_can::derive::unbind::<Self, Self::Unbounded>(self) }
+            }
+        }
+    }
+}
+
+/// A lifetime for the generated code named `name`, with underscores added
+/// until it doesn't collide with any of the item's own lifetime parameters.
+fn fresh_lifetime(generics: &syn::Generics, name: &str) -> syn::Lifetime {
+    let mut name = name.to_string();
+    while generics
+        .lifetimes()
+        .any(|param| param.lifetime.ident == name)
+    {
+        name.push('_');
+    }
+    syn::Lifetime::new(&format!("'{name}"), Span::mixed_site())
+}
+
+/// Replaces the lifetime parameters of a type with an unbounded lifetime.
+struct Unbinder {
+    lifetimes: Vec<syn::Ident>,
+    unbounded: syn::Lifetime,
+}
+
+impl Fold for Unbinder {
+    fn fold_lifetime(&mut self, node: syn::Lifetime) -> syn::Lifetime {
+        if self.lifetimes.contains(&node.ident) {
+            self.unbounded.clone()
+        } else {
+            node
+        }
+    }
+}
@@ -0,0 +1,2879 @@
+//! The transformation behind [`#[you_can::turn_off_the_borrow_checker]`][OFF],
+//! for use by other proc macros and tools.
+//!
+//! [OFF]: https://docs.rs/you-can/latest/you_can/attr.turn_off_the_borrow_checker.html
+
+use {
+    proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree},
+    quote::{quote, quote_spanned, ToTokens, TokenStreamExt},
+    syn::{fold::Fold, parse_quote_spanned, spanned::Spanned, visit::Visit},
+};
+
+mod derive;
+
+pub use derive::derive_borrow_unchecked;
+
+/// Suppresses the borrow checker for a file (or module), item, expression, or
+/// statement, like `#[turn_off_the_borrow_checker]` does. If the tokens aren't
+/// one of those, returns a `compile_error!` instead.
+pub fn suppress(tokens: TokenStream, options: Options) -> (TokenStream, Report) {
+    let mut suppressor = BorrowCheckerSuppressor::new(options);
+
+    let output = if let Ok(as_file) = syn::parse2(tokens.clone()) {
+        suppressor.fold_file(as_file).to_token_stream()
+    } else if let Ok(as_expr) = syn::parse2(tokens.clone()) {
+        suppressor.fold_expr(as_expr).to_token_stream()
+    } else if let Ok(as_stmt) = syn::parse2(tokens) {
+        let stmts = suppressor.fold_stmts(vec![as_stmt], None);
+        quote! { #(#stmts)* }
+    } else {
+        let error = quote! { compile_error!("unsupported use of #[turn_off_the_borrow_checker]") };
+        return (error, Report::default());
+    };
+
+    (output, suppressor.report)
+}
+
+/// Suppresses the borrow checker for an expression.
+pub fn suppress_expr(expr: syn::Expr, options: Options) -> (syn::Expr, Report) {
+    let mut suppressor = BorrowCheckerSuppressor::new(options);
+    let expr = suppressor.fold_expr(expr);
+    (expr, suppressor.report)
+}
+
+/// Suppresses the borrow checker for a sequence of statements, like the
+/// contents of a block.
+pub fn suppress_stmts(stmts: Vec<syn::Stmt>, options: Options) -> (Vec<syn::Stmt>, Report) {
+    let mut suppressor = BorrowCheckerSuppressor::new(options);
+    let stmts = suppressor.fold_stmts(stmts, None);
+    (stmts, suppressor.report)
+}
+
+/// Removes the `#[keep_the_borrow_checker]` and `#[unchecked]` markers from
+/// code without suppressing anything, so that it compiles the same way it
+/// would without the borrow checker being turned off.
+pub fn strip_markers(tokens: TokenStream) -> TokenStream {
+    let mut stripped = TokenStream::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = tokens.peek() {
+                    let marker = syn::parse2::<syn::Path>(group.stream()).is_ok_and(|path| {
+                        is_marker_path(&path, "keep_the_borrow_checker")
+                            || is_marker_path(&path, "unchecked")
+                    });
+                    if group.delimiter() == Delimiter::Bracket && marker {
+                        tokens.next();
+                        continue;
+                    }
+                }
+                stripped.append(punct);
+            },
+            TokenTree::Group(group) => {
+                let mut inner = Group::new(group.delimiter(), strip_markers(group.stream()));
+                inner.set_span(group.span());
+                stripped.append(inner);
+            },
+            token => stripped.append(token),
+        }
+    }
+    stripped
+}
+
+/// The references that the borrow checker was suppressed for.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// The references outside of any `impl` block or trait.
+    pub references: Vec<SuppressedReference>,
+    /// The references in each method of an `impl` block or trait, which are
+    /// reported separately, by the method's name (like `Type::method`).
+    pub methods: Vec<(String, Vec<SuppressedReference>)>,
+}
+
+/// A reference that the borrow checker was suppressed for.
+#[derive(Debug, Clone)]
+pub struct SuppressedReference {
+    pub span: Span,
+    pub kind: ReferenceKind,
+    /// The path of the item that the reference is in, relative to the code
+    /// that the borrow checker was suppressed for (like `lesson::main`), if
+    /// it's in one.
+    pub item: Option<String>,
+}
+
+/// How a suppressed reference was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// A shared reference expression (`&x`), or a closure capturing by
+    /// shared reference.
+    Shared,
+    /// A mutable reference expression (`&mut x`), or a closure capturing by
+    /// mutable reference.
+    Mut,
+    /// A `ref` binding in a pattern, or a binding that's a shared reference
+    /// because of default binding modes.
+    Ref,
+    /// A `ref mut` binding in a pattern, or a binding that's a mutable
+    /// reference because of default binding modes.
+    RefMut,
+    /// A call in a `let` initializer returning a value that borrows from its
+    /// receiver or argument, like a borrow guard, an iterator, or a pinned
+    /// reference, or a use of `pin!()`.
+    Call,
+}
+
+impl ReferenceKind {
+    /// The syntax for this kind of reference, like `&mut` or `ref`, or
+    /// `call` for a method call.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReferenceKind::Shared => "&",
+            ReferenceKind::Mut => "&mut",
+            ReferenceKind::Ref => "ref",
+            ReferenceKind::RefMut => "ref mut",
+            ReferenceKind::Call => "call",
+        }
+    }
+}
+
+impl BorrowCheckerSuppressor {
+    fn new(options: Options) -> Self {
+        BorrowCheckerSuppressor {
+            options,
+            report: Report::default(),
+            scopes: vec![vec![]],
+            generated_idents: 0,
+            owner: None,
+            path: vec![],
+            marked: false,
+            in_closure: false,
+        }
+    }
+}
+
+/// Replaces all references (&T or &mut T) with unbounded references by wrapping
+/// them in calls to you_can::borrow_unchecked().
+#[derive(Debug, Default)]
+struct BorrowCheckerSuppressor {
+    options: Options,
+    /// The references we've suppressed so far.
+    report: Report,
+    /// The local bindings that are currently in scope, innermost last, and
+    /// the runtime loans on the references they hold, if we're checking them.
+    scopes: Vec<Vec<(syn::Ident, Binding, Option<syn::Ident>)>>,
+    /// The number of identifiers we've generated for hoisted values and
+    /// captured variables, used to give them unique names.
+    generated_idents: usize,
+    /// The name of the type or trait whose items we're in, if any.
+    owner: Option<String>,
+    /// The names of the items we're in, outermost first.
+    path: Vec<String>,
+    /// Whether we're in code marked with `#[unchecked]`.
+    marked: bool,
+    /// Whether we're in a closure or async block, where we don't check
+    /// references at runtime, since making it `move` could move the loans.
+    in_closure: bool,
+}
+
+/// Methods that conventionally take `self` by value, whose receivers we must
+/// not replace with a place behind an unbounded reference, because they would
+/// then need to move out of it.
+const CONSUMING_METHODS: &[&str] = &[
+    "and",
+    "and_then",
+    "chain",
+    "cloned",
+    "collect",
+    "copied",
+    "count",
+    "enumerate",
+    "err",
+    "expect",
+    "expect_err",
+    "filter",
+    "filter_map",
+    "flat_map",
+    "flatten",
+    "fold",
+    "for_each",
+    "last",
+    "map",
+    "map_err",
+    "map_or",
+    "map_or_else",
+    "max",
+    "min",
+    "ok",
+    "ok_or",
+    "ok_or_else",
+    "or",
+    "or_else",
+    "peekable",
+    "product",
+    "rev",
+    "skip",
+    "step_by",
+    "sum",
+    "unzip",
+    "zip",
+];
+
+/// Methods that conventionally return a value that borrows from their
+/// receiver, like a borrow guard, an iterator, or a pinned reference, which we
+/// unbind if they're called in a `let` initializer.
+const BORROWING_METHODS: &[&str] = &[
+    "as_mut",
+    "borrow",
+    "borrow_mut",
+    "bytes",
+    "char_indices",
+    "chars",
+    "chunks",
+    "chunks_mut",
+    "drain",
+    "entry",
+    "first_mut",
+    "get_mut",
+    "iter",
+    "iter_mut",
+    "keys",
+    "last_mut",
+    "lines",
+    "lock",
+    "read",
+    "split_whitespace",
+    "try_borrow",
+    "try_borrow_mut",
+    "try_lock",
+    "try_read",
+    "try_write",
+    "values",
+    "values_mut",
+    "windows",
+    "write",
+];
+
+/// Methods that conventionally take `self` by shared reference, other than the
+/// borrowing methods.
+const INSPECTING_METHODS: &[&str] = &[
+    "as_ptr",
+    "as_ref",
+    "as_slice",
+    "as_str",
+    "capacity",
+    "clone",
+    "contains",
+    "contains_key",
+    "ends_with",
+    "first",
+    "get",
+    "is_empty",
+    "is_none",
+    "is_some",
+    "len",
+    "starts_with",
+    "to_owned",
+    "to_string",
+    "to_vec",
+];
+
+/// Methods that borrow their receiver immutably, but return a value that
+/// allows mutation through it.
+const LOCKING_METHODS: &[&str] = &["lock", "try_lock", "try_write", "write"];
+
+/// Methods that conventionally take `self` by mutable reference, whose
+/// receivers we treat as mutably borrowed when checking references at runtime.
+/// We assume that other methods only borrow their receivers immutably.
+const MUTATING_METHODS: &[&str] = &[
+    "append",
+    "clear",
+    "dedup",
+    "drain",
+    "entry",
+    "extend",
+    "fill",
+    "insert",
+    "next",
+    "pop",
+    "push",
+    "push_str",
+    "remove",
+    "replace",
+    "reserve",
+    "resize",
+    "retain",
+    "reverse",
+    "sort",
+    "sort_by",
+    "sort_by_key",
+    "sort_unstable",
+    "split_off",
+    "swap",
+    "swap_remove",
+    "take",
+    "truncate",
+];
+
+/// Methods that take `self` by reference on some common types, but by value on
+/// others, like `Option::take()` and `Iterator::take()`.
+const AMBIGUOUS_METHODS: &[&str] = &["bytes", "lines", "take"];
+
+/// The standard library collections, whose methods we trust the names of to
+/// tell us whether they take `self` by reference.
+const COLLECTION_TYPES: &[&str] = &[
+    "BTreeMap",
+    "BTreeSet",
+    "BinaryHeap",
+    "HashMap",
+    "HashSet",
+    "LinkedList",
+    "String",
+    "Vec",
+    "VecDeque",
+];
+
+impl BorrowCheckerSuppressor {
+    /// Wraps a reference expression in a call to `borrow_unchecked()`.
+    fn unbind_reference(&mut self, node: syn::ExprReference) -> syn::Expr {
+        if !self.suppresses_reference(&node) {
+            return syn::Expr::Reference(node);
+        }
+        self.suppressed(node.span(), reference_kind(&node));
+        syn::Expr::Block(parse_quote_spanned! { node.span() =>
+            {
+                let r#ref = #node;
+                unsafe { ::you_can::borrow_unchecked(r#ref) }
+            }
+        })
+    }
+
+    /// Replaces the receiver of a method call with one that is reborrowed
+    /// through an unbounded reference, so that any implicit autoref the method
+    /// call adds will be unbounded too. We can only do this for receivers that
+    /// are a local binding (or a field of one) whose mutability we know, and
+    /// if the binding isn't a reference, for methods that we know take `self`
+    /// by reference.
+    fn unbind_receiver(&mut self, method: &syn::Ident, receiver: syn::Expr) -> syn::Expr {
+        if is_consuming(method) {
+            return receiver;
+        }
+
+        let mut root = &receiver;
+        let mut is_binding = true;
+        loop {
+            match root {
+                syn::Expr::Paren(syn::ExprParen { expr, .. }) => root = expr,
+                syn::Expr::Field(syn::ExprField { base, .. }) => {
+                    is_binding = false;
+                    root = base;
+                },
+                _ => break,
+            }
+        }
+        let binding = match root {
+            syn::Expr::Path(path) if path.attrs.is_empty() && path.qself.is_none() =>
+                match path.path.get_ident() {
+                    Some(ident) => self.binding(ident),
+                    None => Binding::Unknown,
+                },
+            _ => Binding::Unknown,
+        };
+
+        let span = receiver.span();
+        let reference: syn::ExprReference = match (binding, is_binding) {
+            // If the receiver is owned, the method might need to move it.
+            (Binding::Mutable, _) if !takes_self_by_reference(method) => return receiver,
+            (Binding::MutableCollection, _) if !collection_takes_self_by_reference(method) =>
+                return receiver,
+            (Binding::Mutable | Binding::MutableCollection, _) | (Binding::MutReference, false) =>
+                parse_quote_spanned! { span => &mut #receiver },
+            (Binding::SharedReference, false) => parse_quote_spanned! { span => &#receiver },
+            (Binding::MutReference, true) => parse_quote_spanned! { span => &mut *#receiver },
+            (Binding::SharedReference, true) => parse_quote_spanned! { span => &*#receiver },
+            (Binding::Unknown, _) => return receiver,
+        };
+        if !self.suppresses_reference(&reference) {
+            return receiver;
+        }
+        let unbound = self.unbind_reference(reference);
+
+        if is_binding && !binding.is_mutable() {
+            // The binding is itself a reference, so we can pass the unbounded
+            // reference along by value, in case the method takes it as `self`.
+            parse_quote_spanned! { span => (#unbound) }
+        } else {
+            parse_quote_spanned! { span => (*#unbound) }
+        }
+    }
+
+    /// Rebinds the local variables that a closure or async block captures to
+    /// unbounded references, which we then move into it instead. We only do
+    /// this if we know how every captured variable was declared, and if it
+    /// only uses the owned ones by reference, so that making it `move` can't
+    /// change how anything else is captured. An `async move` block already
+    /// moves the owned ones into itself, so we only rebind its references,
+    /// and the values of the ones without `mut`, which might be references
+    /// too (but are left alone if they aren't).
+    fn unbind_captures(&mut self, node: syn::Expr) -> syn::Expr {
+        let (capturing_by_reference, moving) = match &node {
+            syn::Expr::Closure(closure) => (
+                closure.capture.is_none() && closure.asyncness.is_none(),
+                false,
+            ),
+            syn::Expr::Async(block) => (block.capture.is_none(), block.capture.is_some()),
+            _ => (false, false),
+        };
+        if !capturing_by_reference && !moving {
+            return syn::fold::fold_expr(self, node);
+        }
+
+        let mut capture_collector = CaptureCollector::default();
+        capture_collector.visit_expr(&node);
+
+        let mut captures: Vec<(syn::Ident, Binding)> = vec![];
+        for &(ref ident, usage) in &capture_collector.uses {
+            let binding = match self.lookup(ident) {
+                Some(binding) => binding,
+                None => continue,
+            };
+            let owned = binding.is_mutable() || binding == Binding::Unknown;
+            if moving && binding.is_mutable() {
+                continue;
+            }
+            let by_reference = match usage {
+                CaptureUse::Reference => true,
+                CaptureUse::CollectionReceiver => binding == Binding::MutableCollection,
+                CaptureUse::Value => false,
+            };
+            let shadowed = capture_collector.bound.contains(ident);
+            let mutable = binding.is_mutable() || binding == Binding::MutReference;
+            let suppressed = self.suppresses(Some(mutable), Some(ident));
+            if shadowed || !suppressed || (owned && !moving && !by_reference) {
+                return syn::fold::fold_expr(self, node);
+            }
+            if !captures.iter().any(|(captured, _)| captured == ident) {
+                captures.push((ident.clone(), binding));
+            }
+        }
+        if capture_collector.opaque || captures.is_empty() {
+            return syn::fold::fold_expr(self, node);
+        }
+
+        let mut rebinds: Vec<syn::Stmt> = vec![];
+        let mut replacements = vec![];
+        for (ident, binding) in captures {
+            let unbound = self.generated_ident("captured");
+            if moving && binding == Binding::Unknown {
+                self.suppressed(ident.span(), ReferenceKind::Call);
+                rebinds.push(parse_quote_spanned! { ident.span() =>
+                    let #unbound = {
+                        #[allow(unused_imports)]
+                        use ::you_can::guards::{KeepTag as _, UnbindTag as _};
+                        unsafe { (&::you_can::guards::Probe(&#ident)).tag().unbind(#ident) }
+                    };
+                });
+                replacements.push((ident, unbound, false));
+                continue;
+            }
+            let reference = match binding {
+                Binding::Mutable | Binding::MutableCollection =>
+                    quote_spanned! { ident.span() => &mut #ident },
+                Binding::MutReference => quote_spanned! { ident.span() => &mut *#ident },
+                Binding::SharedReference => quote_spanned! { ident.span() => &*#ident },
+                Binding::Unknown => quote_spanned! { ident.span() => &#ident },
+            };
+            let kind = match binding {
+                Binding::SharedReference | Binding::Unknown => ReferenceKind::Shared,
+                _ => ReferenceKind::Mut,
+            };
+            self.suppressed(ident.span(), kind);
+            rebinds.push(parse_quote_spanned! { ident.span() =>
+                let #unbound = unsafe { ::you_can::borrow_unchecked(#reference) };
+            });
+            replacements.push((
+                ident,
+                unbound,
+                binding.is_mutable() || binding == Binding::Unknown,
+            ));
+        }
+
+        let node = CaptureReplacer { replacements }.fold_expr(node);
+        let node = match syn::fold::fold_expr(self, node) {
+            syn::Expr::Closure(closure) => syn::Expr::Closure(syn::ExprClosure {
+                capture: Some(syn::Token![move](closure.or1_token.span)),
+                ..closure
+            }),
+            syn::Expr::Async(block) => syn::Expr::Async(syn::ExprAsync {
+                capture: Some(syn::Token![move](block.async_token.span)),
+                ..block
+            }),
+            node => node,
+        };
+        parse_quote_spanned! { node.span() =>
+            {
+                #(#rebinds)*
+                #node
+            }
+        }
+    }
+
+    /// Returns statements rebinding each `ref` or `ref mut` binding in a
+    /// pattern to an unbounded reference. If the pattern is being matched
+    /// against a reference, its other bindings will also be references due to
+    /// default binding modes ("match ergonomics"), so we rebind those too.
+    fn rebind_refs(&mut self, pat: &syn::Pat, matched: &Matched) -> Vec<syn::Stmt> {
+        let mut ref_collector = RefCollector {
+            matching_reference: matched.reference,
+            ..RefCollector::default()
+        };
+        ref_collector.visit_pat(pat);
+        let mut rebinds = vec![];
+        for (ident, mutable, span) in ref_collector.refs {
+            if self.suppresses(Some(mutable), matched.root.as_ref()) {
+                let kind = if mutable {
+                    ReferenceKind::RefMut
+                } else {
+                    ReferenceKind::Ref
+                };
+                self.suppressed(span, kind);
+                rebinds.push(parse_quote_spanned! { ident.span() =>
+                    let #ident = unsafe { ::you_can::borrow_unchecked(#ident) };
+                });
+            }
+        }
+        rebinds
+    }
+
+    /// Returns statements rebinding each `ref` or `ref mut` binding in a
+    /// function's parameters.
+    fn rebind_params(&mut self, sig: &mut syn::Signature) -> Vec<syn::Stmt> {
+        let mut rebinds = vec![];
+        for input in &mut sig.inputs {
+            if let syn::FnArg::Typed(typed) = input {
+                let marked = is_marked_unchecked(&mut typed.attrs);
+                rebinds.extend(self.marked(marked, |this| {
+                    this.rebind_refs(&typed.pat, &Matched::default())
+                }));
+            }
+        }
+        rebinds
+    }
+
+    /// Folds a sequence of statements, following each `let` statement with
+    /// the rebinding of any `ref` or `ref mut` bindings it introduced, and
+    /// folding the tail as an extending position if the block is in one.
+    fn fold_stmts(
+        &mut self,
+        stmts: Vec<syn::Stmt>,
+        mut extending: Option<&mut Hoisted>,
+    ) -> Vec<syn::Stmt> {
+        let mut folded = vec![];
+        let tail = stmts.len().checked_sub(1);
+        for (index, stmt) in stmts.into_iter().enumerate() {
+            let (checks, deferred_checks) = self.check_accesses(&stmt);
+            folded.extend(checks);
+            match stmt {
+                syn::Stmt::Local(mut local) => {
+                    if keeps_the_borrow_checker(&mut local.attrs) {
+                        self.declare(&local.pat);
+                        folded.push(syn::Stmt::Local(local));
+                        continue;
+                    }
+                    let marked = is_marked_unchecked(&mut local.attrs);
+                    let matched = local
+                        .init
+                        .as_ref()
+                        .map(|(_, init)| self.matched(init))
+                        .unwrap_or_default();
+                    let (local, rebinds, loan) = self.marked(marked, |this| {
+                        let loan = this.checked_loan(&local);
+                        let local = this.fold_let(local, &mut folded);
+                        let rebinds = this.rebind_refs(&local.pat, &matched);
+                        (local, rebinds, loan)
+                    });
+                    folded.push(syn::Stmt::Local(local));
+                    folded.extend(rebinds);
+                    if let Some((ident, loan, stmt)) = loan {
+                        self.lend(&ident, loan);
+                        folded.push(stmt);
+                    }
+                },
+                syn::Stmt::Semi(syn::Expr::Verbatim(tokens), semi_token) =>
+                    match syn::parse2::<LocalElse>(tokens.clone()) {
+                        Ok(mut local_else) => {
+                            if keeps_the_borrow_checker(&mut local_else.attrs) {
+                                self.declare(&local_else.pat);
+                                folded.push(syn::Stmt::Semi(
+                                    syn::Expr::Verbatim(local_else.into_token_stream()),
+                                    semi_token,
+                                ));
+                                continue;
+                            }
+                            let marked = is_marked_unchecked(&mut local_else.attrs);
+                            let matched = self.matched(&local_else.init);
+                            let (local_else, rebinds) = self.marked(marked, |this| {
+                                let local_else = LocalElse {
+                                    init: this.fold_initializer(local_else.init, &mut folded),
+                                    diverge: this.fold_block(local_else.diverge),
+                                    ..local_else
+                                };
+                                this.declare(&local_else.pat);
+                                let rebinds = this.rebind_refs(&local_else.pat, &matched);
+                                (local_else, rebinds)
+                            });
+                            folded.push(syn::Stmt::Semi(
+                                syn::Expr::Verbatim(local_else.into_token_stream()),
+                                semi_token,
+                            ));
+                            folded.extend(rebinds);
+                        },
+                        Err(_) =>
+                            folded.push(syn::Stmt::Semi(syn::Expr::Verbatim(tokens), semi_token)),
+                    },
+                syn::Stmt::Expr(expr) if Some(index) == tail && extending.is_some() => {
+                    let hoisted = extending.take().expect("checked above");
+                    let expr = self.fold_extending_tail(expr, hoisted, &mut folded);
+                    folded.push(syn::Stmt::Expr(expr));
+                },
+                stmt => folded.push(self.fold_stmt(stmt)),
+            }
+            folded.extend(deferred_checks);
+        }
+        folded
+    }
+
+    /// If we're checking references at runtime, returns the statements
+    /// reporting the accesses that a statement makes to local variables, and
+    /// checking the loans it uses, to go before it. Assignments that might
+    /// initialize a variable are reported after it instead.
+    fn check_accesses(&self, stmt: &syn::Stmt) -> (Vec<syn::Stmt>, Vec<syn::Stmt>) {
+        if !self.options.checked || self.in_closure {
+            return Default::default();
+        }
+
+        let mut access_collector = AccessCollector::default();
+        match stmt {
+            syn::Stmt::Semi(syn::Expr::Verbatim(tokens), _) => {
+                if let Ok(local_else) = syn::parse2::<LocalElse>(tokens.clone()) {
+                    access_collector.visit_expr(&local_else.init);
+                }
+            },
+            stmt => access_collector.visit_stmt(stmt),
+        }
+
+        let mut checks: Vec<syn::Stmt> = vec![];
+        let mut deferred_checks: Vec<syn::Stmt> = vec![];
+        for access in access_collector.accesses {
+            let binding = match self.lookup(&access.root) {
+                Some(binding) => binding,
+                None => continue,
+            };
+            let span = access.span;
+            let root = &access.root;
+            let whole = access.fields.is_empty() && !access.projected;
+            let place = format!("{root}{}", access.fields);
+
+            let (check, deferred): (syn::Stmt, bool) = match (self.loan(root), access.usage) {
+                (Some(_), PlaceUse::Write) if whole => continue,
+                (Some(loan), _) => (
+                    parse_quote_spanned! { span => ::you_can::checked::Loan::check(&#loan); },
+                    false,
+                ),
+                (None, usage) => {
+                    let is_reference =
+                        matches!(binding, Binding::MutReference | Binding::SharedReference);
+                    let root = if is_reference {
+                        quote_spanned! { span => ::core::ptr::addr_of!(*#root) }
+                    } else {
+                        quote_spanned! { span => ::core::ptr::addr_of!(#root) }
+                    };
+                    match usage {
+                        // Copying a reference doesn't access what it points to.
+                        PlaceUse::Value if is_reference && whole => continue,
+                        PlaceUse::Value if !is_reference && !access.projected => {
+                            let value = &access.place;
+                            (
+                                parse_quote_spanned! { span =>
+                                    ::you_can::checked::value(
+                                        #root,
+                                        ::core::ptr::addr_of!(#value),
+                                        #place,
+                                    );
+                                },
+                                false,
+                            )
+                        },
+                        PlaceUse::Value | PlaceUse::Read => (
+                            parse_quote_spanned! { span =>
+                                ::you_can::checked::read(#root, #place);
+                            },
+                            false,
+                        ),
+                        PlaceUse::Write => (
+                            parse_quote_spanned! { span =>
+                                ::you_can::checked::write(#root, #place);
+                            },
+                            whole && !is_reference && matches!(stmt, syn::Stmt::Semi(..)),
+                        ),
+                    }
+                },
+            };
+
+            let checks = if deferred {
+                &mut deferred_checks
+            } else {
+                &mut checks
+            };
+            let check_string = check.to_token_stream().to_string();
+            if !checks
+                .iter()
+                .any(|existing| existing.to_token_stream().to_string() == check_string)
+            {
+                checks.push(check);
+            }
+        }
+        (checks, deferred_checks)
+    }
+
+    /// If we're checking references at runtime, and a `let` statement binds a
+    /// single variable to a reference that we're suppressing, declares a loan
+    /// for it. Returns the variable, the loan, and the statement registering
+    /// the loan, to go after the `let` statement.
+    fn checked_loan(&mut self, local: &syn::Local) -> Option<(syn::Ident, syn::Ident, syn::Stmt)> {
+        if !self.options.checked || self.in_closure {
+            return None;
+        }
+        let (ident, reference) = match (&local.pat, local.init.as_ref().map(|(_, init)| &**init)) {
+            (
+                syn::Pat::Ident(syn::PatIdent {
+                    by_ref: None,
+                    mutability: None,
+                    subpat: None,
+                    ident,
+                    ..
+                }),
+                Some(syn::Expr::Reference(reference)),
+            ) if self.suppresses_reference(reference) => (ident, reference),
+            _ => return None,
+        };
+        let (root, fields, _) = place_parts(&reference.expr)?;
+        let binding = self.lookup(root)?;
+
+        let span = reference.span();
+        let place = format!("{root}{fields}");
+        let mutable = reference.mutability.is_some();
+        let root = match binding {
+            Binding::MutReference | Binding::SharedReference =>
+                quote_spanned! { span => ::core::ptr::addr_of!(*#root) },
+            _ => quote_spanned! { span => ::core::ptr::addr_of!(#root) },
+        };
+        let loan = self.generated_ident("_loan");
+        let stmt = parse_quote_spanned! { span =>
+            let #loan = ::you_can::checked::loan(#root, #place, #mutable);
+        };
+        Some((ident.clone(), loan, stmt))
+    }
+
+    /// Folds a `let` statement, adding any temporaries that need to be hoisted
+    /// out of its initializer to `hoisted`.
+    fn fold_let(&mut self, node: syn::Local, hoisted: &mut Vec<syn::Stmt>) -> syn::Local {
+        // A reference binding initialized with an explicit reference doesn't
+        // need `mut` or a type annotation to tell us what it is.
+        let binding = match (&node.pat, node.init.as_ref().map(|(_, init)| &**init)) {
+            (
+                syn::Pat::Ident(syn::PatIdent {
+                    by_ref: None,
+                    mutability: None,
+                    subpat: None,
+                    ident,
+                    ..
+                }),
+                Some(syn::Expr::Reference(reference)),
+            ) => Some((
+                ident.clone(),
+                if reference.mutability.is_some() {
+                    Binding::MutReference
+                } else {
+                    Binding::SharedReference
+                },
+            )),
+            _ => None,
+        };
+        // Nor does a `mut` binding's type or initializer, if it's one of the
+        // standard library collections.
+        let binding = binding.or_else(|| {
+            let init = node.init.as_ref().map(|(_, init)| &**init);
+            let (pat, ty) = match &node.pat {
+                syn::Pat::Type(typed) => (&*typed.pat, Some(&*typed.ty)),
+                pat => (pat, None),
+            };
+            match pat {
+                syn::Pat::Ident(syn::PatIdent {
+                    by_ref: None,
+                    mutability: Some(_),
+                    subpat: None,
+                    ident,
+                    ..
+                }) if ty.is_some_and(is_collection_type) || init.is_some_and(is_collection) =>
+                    Some((ident.clone(), Binding::MutableCollection)),
+                _ => None,
+            }
+        });
+
+        let node = syn::Local {
+            init: node.init.map(|(eq_token, init)| {
+                (eq_token, Box::new(self.fold_initializer(*init, hoisted)))
+            }),
+            ..node
+        };
+
+        match binding {
+            Some((ident, binding)) => self
+                .scopes
+                .last_mut()
+                .expect("bindings must be declared in a scope")
+                .push((ident, binding, None)),
+            None => self.declare(&node.pat),
+        }
+
+        node
+    }
+
+    /// Folds the initializer of a `let` statement. If it calls a method that
+    /// returns a borrow guard or an iterator (like `RefCell::borrow_mut()` or
+    /// `Vec::iter()`), we unbind the value it returns. We can't tell what type
+    /// that is, so we leave it to autoref-based specialization to only unbind
+    /// values that implement `BorrowUnchecked`, and leave anything else alone.
+    fn fold_initializer(&mut self, mut node: syn::Expr, hoisted: &mut Vec<syn::Stmt>) -> syn::Expr {
+        if keeps_the_borrow_checker(&mut node) {
+            return node;
+        }
+        if is_marked_unchecked(&mut node) {
+            return self.marked(true, |this| this.fold_initializer(node, hoisted));
+        }
+        let span = node.span();
+        if is_pin_macro(&node) && self.suppresses(Some(true), None) {
+            // `pin!()` pins its argument in a temporary that's extended to the
+            // end of the block, so we hoist it like a reference to one.
+            let node = self.fold_expr(node);
+            self.suppressed(span, ReferenceKind::Call);
+            let temporary = self.generated_ident("pinned");
+            hoisted.push(parse_quote_spanned! { span =>
+                let #temporary = #node;
+            });
+            return parse_quote_spanned! { span =>
+                unsafe { ::you_can::borrow_unchecked(#temporary) }
+            };
+        }
+
+        let returned = borrowing_call(&node)
+            .is_some_and(|(borrowed, mutable)| self.suppresses(mutable, place_root(borrowed)));
+        let mut extending = Hoisted::default();
+        let node = self.fold_extending(node, &mut extending);
+        hoisted.extend(extending.declarations);
+        hoisted.extend(extending.stmts);
+        if !returned {
+            return node;
+        }
+
+        self.suppressed(span, ReferenceKind::Call);
+        parse_quote_spanned! { span =>
+            {
+                let r#returned = #node;
+                #[allow(unused_imports)]
+                use ::you_can::guards::{KeepTag as _, UnbindTag as _};
+                unsafe { (&::you_can::guards::Probe(&r#returned)).tag().unbind(r#returned) }
+            }
+        }
+    }
+
+    /// Folds an expression in an extending position of a `let` initializer,
+    /// where a reference to a temporary value keeps that temporary alive until
+    /// the end of the enclosing block. Wrapping those references would drop
+    /// the temporary early, so we hoist them into their own `let` statements
+    /// (where they're still extended) and unbind those instead. Anything that
+    /// would have been evaluated before them gets hoisted too, to preserve the
+    /// order of evaluation. The tails of blocks and the branches of `if` and
+    /// `match` expressions are extending positions too, but we can only hoist
+    /// the temporaries in them to the start of their own block, so we declare
+    /// them before the `let` statement instead, and assign them there.
+    fn fold_extending(&mut self, mut node: syn::Expr, hoisted: &mut Hoisted) -> syn::Expr {
+        if keeps_the_borrow_checker(&mut node) {
+            return node;
+        }
+        if is_marked_unchecked(&mut node) {
+            return self.marked(true, |this| this.fold_extending(node, hoisted));
+        }
+        match node {
+            syn::Expr::Reference(node)
+                if !is_place(&node.expr) && self.suppresses_reference(&node) =>
+            {
+                let node = syn::ExprReference {
+                    expr: Box::new(self.fold_extending(*node.expr, hoisted)),
+                    ..node
+                };
+                let span = node.span();
+                self.suppressed(span, reference_kind(&node));
+                let temporary = self.generated_ident("hoisted");
+                if hoisted.in_tail {
+                    let syn::ExprReference {
+                        mutability, expr, ..
+                    } = node;
+                    hoisted.declarations.push(parse_quote_spanned! { span =>
+                        let #mutability #temporary;
+                    });
+                    hoisted.stmts.push(parse_quote_spanned! { span =>
+                        #temporary = #expr;
+                    });
+                    return parse_quote_spanned! { span =>
+                        unsafe { ::you_can::borrow_unchecked(&#mutability #temporary) }
+                    };
+                }
+                hoisted.stmts.push(parse_quote_spanned! { span =>
+                    let #temporary = #node;
+                });
+                parse_quote_spanned! { span =>
+                    unsafe { ::you_can::borrow_unchecked(#temporary) }
+                }
+            },
+            syn::Expr::Block(node) => syn::Expr::Block(syn::ExprBlock {
+                block: self.fold_scoped_block(node.block, Some(hoisted)),
+                ..node
+            }),
+            syn::Expr::Unsafe(node) => syn::Expr::Unsafe(syn::ExprUnsafe {
+                block: self.fold_scoped_block(node.block, Some(hoisted)),
+                ..node
+            }),
+            syn::Expr::If(node) => syn::Expr::If(self.fold_if(node, Some(hoisted))),
+            syn::Expr::Match(node) => syn::Expr::Match(self.fold_match(node, Some(hoisted))),
+            syn::Expr::Paren(node) => syn::Expr::Paren(syn::ExprParen {
+                expr: Box::new(self.fold_extending(*node.expr, hoisted)),
+                ..node
+            }),
+            syn::Expr::Cast(node) => syn::Expr::Cast(syn::ExprCast {
+                expr: Box::new(self.fold_extending(*node.expr, hoisted)),
+                ..node
+            }),
+            syn::Expr::Tuple(node) => syn::Expr::Tuple(syn::ExprTuple {
+                elems: self.fold_extending_operands(node.elems, hoisted),
+                ..node
+            }),
+            syn::Expr::Array(node) => syn::Expr::Array(syn::ExprArray {
+                elems: self.fold_extending_operands(node.elems, hoisted),
+                ..node
+            }),
+            syn::Expr::Struct(node) => {
+                let (members, exprs): (Vec<_>, Vec<_>) = node
+                    .fields
+                    .into_pairs()
+                    .map(|pair| {
+                        let (field, comma) = pair.into_tuple();
+                        (
+                            (field.attrs, field.member, field.colon_token, comma),
+                            field.expr,
+                        )
+                    })
+                    .unzip();
+                let exprs = self.fold_extending_operands(exprs, hoisted);
+                let fields = members
+                    .into_iter()
+                    .zip(exprs)
+                    .map(|((attrs, member, colon_token, comma), expr)| {
+                        // Shorthand fields need a colon if their value changed.
+                        let colon_token = match (&member, &expr) {
+                            (syn::Member::Named(name), syn::Expr::Path(path))
+                                if path.path.is_ident(name) =>
+                                colon_token,
+                            _ => colon_token.or_else(|| Some(Default::default())),
+                        };
+                        syn::punctuated::Pair::new(
+                            syn::FieldValue {
+                                attrs,
+                                member,
+                                colon_token,
+                                expr,
+                            },
+                            comma,
+                        )
+                    })
+                    .collect();
+                syn::Expr::Struct(syn::ExprStruct {
+                    fields,
+                    rest: node.rest.map(|rest| Box::new(self.fold_expr(*rest))),
+                    ..node
+                })
+            },
+            node => self.fold_expr(node),
+        }
+    }
+
+    /// Folds the operands of a tuple, array, or struct expression in an
+    /// extending position, in order.
+    fn fold_extending_operands(
+        &mut self,
+        operands: impl IntoIterator<Item = syn::Expr>,
+        hoisted: &mut Hoisted,
+    ) -> syn::punctuated::Punctuated<syn::Expr, syn::Token![,]> {
+        let operands: Vec<(Vec<syn::Stmt>, syn::Expr)> = operands
+            .into_iter()
+            .map(|operand| {
+                let mut operand_hoisted = Hoisted {
+                    in_tail: hoisted.in_tail,
+                    ..Hoisted::default()
+                };
+                let operand = self.fold_extending(operand, &mut operand_hoisted);
+                hoisted.declarations.extend(operand_hoisted.declarations);
+                (operand_hoisted.stmts, operand)
+            })
+            .collect();
+        let last_hoisting = operands
+            .iter()
+            .rposition(|(operand_hoisted, _)| !operand_hoisted.is_empty());
+
+        let mut folded = syn::punctuated::Punctuated::new();
+        for (index, (operand_hoisted, operand)) in operands.into_iter().enumerate() {
+            hoisted.stmts.extend(operand_hoisted);
+            let is_pure = matches!(
+                operand,
+                syn::Expr::Lit(_) | syn::Expr::Path(_) | syn::Expr::Closure(_)
+            );
+            if !is_pure && last_hoisting.is_some_and(|last_hoisting| index < last_hoisting) {
+                let value = self.generated_ident("hoisted");
+                hoisted.stmts.push(parse_quote_spanned! { operand.span() =>
+                    let #value = #operand;
+                });
+                folded.push(parse_quote_spanned! { operand.span() => #value });
+            } else {
+                folded.push(operand);
+            }
+        }
+        folded
+    }
+
+    /// Returns a new hygienic identifier for a value we've introduced.
+    fn generated_ident(&mut self, prefix: &str) -> syn::Ident {
+        self.generated_idents += 1;
+        quote::format_ident!(
+            "{}_{}",
+            prefix,
+            self.generated_idents,
+            span = proc_macro2::Span::mixed_site()
+        )
+    }
+
+    /// Folds an `if` expression, and the tails of its branches as extending
+    /// positions if it's in one.
+    fn fold_if(&mut self, node: syn::ExprIf, mut extending: Option<&mut Hoisted>) -> syn::ExprIf {
+        let syn::ExprIf {
+            attrs,
+            if_token,
+            cond,
+            then_branch,
+            else_branch,
+        } = node;
+        let (cond, then_branch) = match *cond {
+            syn::Expr::Let(expr_let) => {
+                let matched = self.matched(&expr_let.expr);
+                let expr_let = syn::ExprLet {
+                    expr: Box::new(self.fold_expr(*expr_let.expr)),
+                    ..expr_let
+                };
+                let then_branch = self.scoped([&expr_let.pat], |this| {
+                    this.fold_scoped_block(then_branch, extending.as_deref_mut())
+                });
+                let rebinds = self.rebind_refs(&expr_let.pat, &matched);
+                (syn::Expr::Let(expr_let), prepend(rebinds, then_branch))
+            },
+            cond => (
+                self.fold_expr(cond),
+                self.fold_scoped_block(then_branch, extending.as_deref_mut()),
+            ),
+        };
+        let else_branch = else_branch.map(|(else_token, expr)| {
+            let expr = match extending {
+                Some(hoisted) => self.fold_extending(*expr, hoisted),
+                None => self.fold_expr(*expr),
+            };
+            (else_token, Box::new(expr))
+        });
+        syn::ExprIf {
+            attrs,
+            if_token,
+            cond: Box::new(cond),
+            then_branch,
+            else_branch,
+        }
+    }
+
+    /// Folds a `match` expression, and the bodies of its arms as extending
+    /// positions if it's in one.
+    fn fold_match(
+        &mut self,
+        node: syn::ExprMatch,
+        mut extending: Option<&mut Hoisted>,
+    ) -> syn::ExprMatch {
+        let matched = self.matched(&node.expr);
+        let syn::ExprMatch {
+            attrs,
+            match_token,
+            expr,
+            brace_token,
+            arms,
+        } = node;
+        syn::ExprMatch {
+            attrs,
+            match_token,
+            expr: Box::new(self.fold_expr(*expr)),
+            brace_token,
+            arms: arms
+                .into_iter()
+                .map(|arm| self.fold_arm_matching(arm, &matched, extending.as_deref_mut()))
+                .collect(),
+        }
+    }
+
+    /// Folds a block in a new scope, and its tail as an extending position if
+    /// the block is in one.
+    fn fold_scoped_block(
+        &mut self,
+        node: syn::Block,
+        extending: Option<&mut Hoisted>,
+    ) -> syn::Block {
+        self.scoped([], |this| syn::Block {
+            brace_token: node.brace_token,
+            stmts: this.fold_stmts(node.stmts, extending),
+        })
+    }
+
+    /// Folds the tail of a block (or the body of a match arm) in an extending
+    /// position, adding the statements we hoist out of it to `stmts`, which
+    /// go before it in its own block.
+    fn fold_extending_tail(
+        &mut self,
+        node: syn::Expr,
+        hoisted: &mut Hoisted,
+        stmts: &mut Vec<syn::Stmt>,
+    ) -> syn::Expr {
+        let mut tail_hoisted = Hoisted {
+            in_tail: true,
+            ..Hoisted::default()
+        };
+        let node = self.fold_extending(node, &mut tail_hoisted);
+        hoisted.declarations.extend(tail_hoisted.declarations);
+        stmts.extend(tail_hoisted.stmts);
+        node
+    }
+
+    /// Folds a match arm, rebinding the references its pattern binds, and
+    /// folding its body as an extending position if the `match` is in one.
+    fn fold_arm_matching(
+        &mut self,
+        mut node: syn::Arm,
+        matched: &Matched,
+        extending: Option<&mut Hoisted>,
+    ) -> syn::Arm {
+        let marked = is_marked_unchecked(&mut node.attrs);
+        self.marked(marked, |this| {
+            let pat = node.pat.clone();
+            let mut node = this.scoped([&pat], |this| match extending {
+                Some(hoisted) => {
+                    let guard = node
+                        .guard
+                        .map(|(if_token, guard)| (if_token, Box::new(this.fold_expr(*guard))));
+                    let mut stmts = vec![];
+                    let body = this.fold_extending_tail(*node.body, hoisted, &mut stmts);
+                    let body = if stmts.is_empty() {
+                        body
+                    } else {
+                        parse_quote_spanned! { body.span() =>
+                            {
+                                #(#stmts)*
+                                #body
+                            }
+                        }
+                    };
+                    syn::Arm {
+                        guard,
+                        body: Box::new(body),
+                        ..node
+                    }
+                },
+                None => syn::fold::fold_arm(this, node),
+            });
+            let rebinds = this.rebind_refs(&node.pat, matched);
+            if !rebinds.is_empty() {
+                let body = node.body;
+                node.body = parse_quote_spanned! { body.span() =>
+                    {
+                        #(#rebinds)*
+                        #body
+                    }
+                };
+            }
+            node
+        })
+    }
+
+    /// Returns what we know about an (unfolded) expression being matched
+    /// against a pattern. It's obviously a reference if it's a `&` or `&mut`
+    /// expression, or a binding that we know holds a reference.
+    fn matched(&self, expr: &syn::Expr) -> Matched {
+        match expr {
+            syn::Expr::Reference(reference) => Matched {
+                reference: Some(reference.mutability.is_some()),
+                root: place_root(&reference.expr).cloned(),
+            },
+            syn::Expr::Paren(syn::ExprParen { expr, .. }) => self.matched(expr),
+            syn::Expr::Path(path) if path.attrs.is_empty() && path.qself.is_none() => Matched {
+                reference: match path.path.get_ident().map(|ident| self.binding(ident)) {
+                    Some(Binding::MutReference) => Some(true),
+                    Some(Binding::SharedReference) => Some(false),
+                    _ => None,
+                },
+                root: path.path.get_ident().cloned(),
+            },
+            _ => Matched {
+                reference: None,
+                root: place_root(expr).cloned(),
+            },
+        }
+    }
+
+    /// Whether the attribute's arguments say to suppress a reference, given
+    /// whether it's mutable (if we know), and the local variable it refers to
+    /// a place in (if any).
+    fn suppresses(&self, mutable: Option<bool>, root: Option<&syn::Ident>) -> bool {
+        let options = &self.options;
+        let kind = match mutable {
+            Some(true) => !options.shared_only,
+            Some(false) => !options.mut_only,
+            None => !options.shared_only && !options.mut_only,
+        };
+        let named = |names: &Vec<syn::Ident>| root.is_some_and(|root| names.contains(root));
+        (self.marked || !options.marked_only)
+            && kind
+            && options.only.as_ref().is_none_or(named)
+            && !named(&options.except)
+    }
+
+    /// Whether the attribute's arguments say to suppress a reference
+    /// expression.
+    fn suppresses_reference(&self, node: &syn::ExprReference) -> bool {
+        self.suppresses(Some(node.mutability.is_some()), place_root(&node.expr))
+    }
+
+    /// Looks up how a local binding was declared.
+    fn binding(&self, ident: &syn::Ident) -> Binding {
+        self.lookup(ident).unwrap_or(Binding::Unknown)
+    }
+
+    /// Looks up how a local binding was declared, if it's one we've seen.
+    fn lookup(&self, ident: &syn::Ident) -> Option<Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(name, ..)| name == ident)
+            .map(|&(_, binding, _)| binding)
+    }
+
+    /// Looks up the runtime loan on the reference held by a local binding, if
+    /// we're checking it.
+    fn loan(&self, ident: &syn::Ident) -> Option<&syn::Ident> {
+        if self.in_closure {
+            return None;
+        }
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(name, ..)| name == ident)
+            .and_then(|(_, _, loan)| loan.as_ref())
+    }
+
+    /// Records the runtime loan on the reference held by a local binding that
+    /// was just declared.
+    fn lend(&mut self, ident: &syn::Ident, loan: syn::Ident) {
+        if let Some((_, _, entry)) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.iter_mut().rev().find(|(name, ..)| name == ident))
+        {
+            *entry = Some(loan);
+        }
+    }
+
+    /// Declares the bindings introduced by a pattern in the innermost scope.
+    fn declare(&mut self, pat: &syn::Pat) {
+        let mut binding_collector = BindingCollector::default();
+        binding_collector.visit_pat(pat);
+        self.scopes
+            .last_mut()
+            .expect("bindings must be declared in a scope")
+            .extend(
+                binding_collector
+                    .bindings
+                    .into_iter()
+                    .map(|(ident, binding)| (ident, binding, None)),
+            );
+    }
+
+    /// Runs `f` in a new scope declaring the bindings of the given patterns.
+    fn scoped<'pat, T>(
+        &mut self,
+        pats: impl IntoIterator<Item = &'pat syn::Pat>,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        self.scopes.push(vec![]);
+        for pat in pats {
+            self.declare(pat);
+        }
+        let output = f(self);
+        self.scopes.pop();
+        output
+    }
+
+    /// Runs `f` for the body of a function, which can't see any of the local
+    /// bindings from outside of it.
+    fn fn_scoped<T>(&mut self, sig: &syn::Signature, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer_scopes = std::mem::take(&mut self.scopes);
+        let mut params = vec![];
+        for input in &sig.inputs {
+            match input {
+                syn::FnArg::Receiver(receiver) => params.push((
+                    syn::Ident::new("self", receiver.self_token.span),
+                    match (&receiver.reference, &receiver.mutability) {
+                        (Some(_), Some(_)) => Binding::MutReference,
+                        (Some(_), None) => Binding::SharedReference,
+                        (None, Some(_)) => Binding::Mutable,
+                        (None, None) => Binding::Unknown,
+                    },
+                    None,
+                )),
+                syn::FnArg::Typed(typed) => {
+                    let mut binding_collector = BindingCollector::default();
+                    binding_collector.visit_pat_type(typed);
+                    params.extend(
+                        binding_collector
+                            .bindings
+                            .into_iter()
+                            .map(|(ident, binding)| (ident, binding, None)),
+                    );
+                },
+            }
+        }
+        self.scopes.push(params);
+        let output = f(self);
+        self.scopes = outer_scopes;
+        output
+    }
+
+    /// Records a reference that we've suppressed.
+    fn suppressed(&mut self, span: Span, kind: ReferenceKind) {
+        let item = (!self.path.is_empty()).then(|| self.path.join("::"));
+        self.report
+            .references
+            .push(SuppressedReference { span, kind, item });
+    }
+
+    /// Runs `f` for an item with a name, which goes in the paths of the items
+    /// that suppressed references are reported in.
+    fn named<T>(&mut self, name: &syn::Ident, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(name.to_string());
+        let output = f(self);
+        self.path.pop();
+        output
+    }
+
+    /// Runs `f` for a method, reporting the references it suppresses
+    /// separately from the rest.
+    fn method_reported<T>(&mut self, method: &syn::Ident, f: impl FnOnce(&mut Self) -> T) -> T {
+        let suppressed = self.report.references.len();
+        let output = self.named(method, f);
+        let spans = self.report.references.split_off(suppressed);
+        let owner = self.owner.as_deref().unwrap_or("Self");
+        self.report
+            .methods
+            .push((format!("{owner}::{method}"), spans));
+        output
+    }
+
+    /// Runs `f` for the items of an impl or trait.
+    fn owned_by<T>(&mut self, owner: String, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(owner.clone());
+        let outer_owner = self.owner.replace(owner);
+        let output = f(self);
+        self.path.pop();
+        self.owner = outer_owner;
+        output
+    }
+
+    /// Runs `f` for code that's marked with `#[unchecked]`, if `marked`.
+    fn marked<T>(&mut self, marked: bool, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer_marked = self.marked;
+        self.marked |= marked;
+        let output = f(self);
+        self.marked = outer_marked;
+        output
+    }
+}
+
+impl Fold for BorrowCheckerSuppressor {
+    fn fold_expr(&mut self, mut node: syn::Expr) -> syn::Expr {
+        if keeps_the_borrow_checker(&mut node) {
+            return node;
+        }
+        if is_marked_unchecked(&mut node) {
+            return self.marked(true, |this| this.fold_expr(node));
+        }
+        match node {
+            syn::Expr::Reference(node) => {
+                let node = syn::fold::fold_expr_reference(self, node);
+                self.unbind_reference(node)
+            },
+            syn::Expr::MethodCall(node) => {
+                let mut node = syn::fold::fold_expr_method_call(self, node);
+                node.receiver = Box::new(self.unbind_receiver(&node.method, *node.receiver));
+                syn::Expr::MethodCall(node)
+            },
+            syn::Expr::Closure(_) | syn::Expr::Async(_) => {
+                let in_closure = std::mem::replace(&mut self.in_closure, true);
+                let node = self.unbind_captures(node);
+                self.in_closure = in_closure;
+                node
+            },
+            _ => syn::fold::fold_expr(self, node),
+        }
+    }
+
+    fn fold_macro(&mut self, node: syn::Macro) -> syn::Macro {
+        let mut args = match MacroArgs::parse(&node) {
+            Some(args) => args,
+            None => return node,
+        };
+        for arg in args.args.iter_mut() {
+            let expr = self.fold_expr(arg.expr.clone());
+            let span = expr.span();
+            arg.expr = match arg.usage {
+                // `write!` and `writeln!` call `write_fmt()` on their first argument.
+                MacroArgUsage::Receiver =>
+                    self.unbind_receiver(&syn::Ident::new("write_fmt", span), expr),
+                // The formatting and assertion macros implicitly borrow their
+                // arguments, so we replace them with unbounded references.
+                MacroArgUsage::Borrowed
+                    if is_place(&expr) && self.suppresses(Some(false), place_root(&expr)) =>
+                {
+                    let unbound = self.unbind_reference(parse_quote_spanned! { span => &#expr });
+                    parse_quote_spanned! { span => *#unbound }
+                },
+                MacroArgUsage::Borrowed | MacroArgUsage::Value => expr,
+            };
+        }
+        args.repeat = args
+            .repeat
+            .map(|(semi_token, len)| (semi_token, self.fold_expr(len)));
+        syn::Macro {
+            tokens: args.into_token_stream(),
+            ..node
+        }
+    }
+
+    fn fold_expr_if(&mut self, node: syn::ExprIf) -> syn::ExprIf {
+        self.fold_if(node, None)
+    }
+
+    fn fold_expr_while(&mut self, node: syn::ExprWhile) -> syn::ExprWhile {
+        match *node.cond {
+            syn::Expr::Let(expr_let) => {
+                let matched = self.matched(&expr_let.expr);
+                let expr_let = syn::ExprLet {
+                    expr: Box::new(self.fold_expr(*expr_let.expr)),
+                    ..expr_let
+                };
+                let body = self.scoped([&expr_let.pat], |this| this.fold_block(node.body));
+                let rebinds = self.rebind_refs(&expr_let.pat, &matched);
+                syn::ExprWhile {
+                    cond: Box::new(syn::Expr::Let(expr_let)),
+                    body: prepend(rebinds, body),
+                    ..node
+                }
+            },
+            cond => syn::ExprWhile {
+                cond: Box::new(self.fold_expr(cond)),
+                body: self.fold_block(node.body),
+                ..node
+            },
+        }
+    }
+
+    fn fold_expr_for_loop(&mut self, node: syn::ExprForLoop) -> syn::ExprForLoop {
+        let expr = Box::new(self.fold_expr(*node.expr));
+        let body = self.scoped([&node.pat], |this| this.fold_block(node.body));
+        let rebinds = self.rebind_refs(&node.pat, &Matched::default());
+        syn::ExprForLoop {
+            expr,
+            body: prepend(rebinds, body),
+            ..node
+        }
+    }
+
+    fn fold_expr_closure(&mut self, mut node: syn::ExprClosure) -> syn::ExprClosure {
+        let marked: Vec<bool> = node.inputs.iter_mut().map(is_marked_unchecked).collect();
+        let inputs = node.inputs.clone();
+        let mut node = self.scoped(&inputs, |this| syn::fold::fold_expr_closure(this, node));
+        let rebinds: Vec<syn::Stmt> = inputs
+            .iter()
+            .zip(marked)
+            .flat_map(|(pat, marked)| {
+                self.marked(marked, |this| this.rebind_refs(pat, &Matched::default()))
+            })
+            .collect();
+        if !rebinds.is_empty() {
+            let body = node.body;
+            node.body = parse_quote_spanned! { body.span() =>
+                {
+                    #(#rebinds)*
+                    #body
+                }
+            };
+        }
+        node
+    }
+
+    fn fold_expr_match(&mut self, node: syn::ExprMatch) -> syn::ExprMatch {
+        self.fold_match(node, None)
+    }
+
+    fn fold_arm(&mut self, node: syn::Arm) -> syn::Arm {
+        self.fold_arm_matching(node, &Matched::default(), None)
+    }
+
+    fn fold_block(&mut self, node: syn::Block) -> syn::Block {
+        self.fold_scoped_block(node, None)
+    }
+
+    fn fold_item(&mut self, mut node: syn::Item) -> syn::Item {
+        if keeps_the_borrow_checker(&mut node) {
+            return node;
+        }
+        let marked = is_marked_unchecked(&mut node);
+        self.marked(marked, |this| syn::fold::fold_item(this, node))
+    }
+
+    fn fold_impl_item(&mut self, mut node: syn::ImplItem) -> syn::ImplItem {
+        if keeps_the_borrow_checker(&mut node) {
+            return node;
+        }
+        let marked = is_marked_unchecked(&mut node);
+        self.marked(marked, |this| syn::fold::fold_impl_item(this, node))
+    }
+
+    fn fold_trait_item(&mut self, mut node: syn::TraitItem) -> syn::TraitItem {
+        if keeps_the_borrow_checker(&mut node) {
+            return node;
+        }
+        let marked = is_marked_unchecked(&mut node);
+        self.marked(marked, |this| syn::fold::fold_trait_item(this, node))
+    }
+
+    fn fold_item_fn(&mut self, node: syn::ItemFn) -> syn::ItemFn {
+        let sig = node.sig.clone();
+        self.named(&sig.ident, |this| {
+            let mut node = this.fn_scoped(&sig, |this| syn::fold::fold_item_fn(this, node));
+            let rebinds = this.rebind_params(&mut node.sig);
+            node.block = Box::new(prepend(rebinds, *node.block));
+            node
+        })
+    }
+
+    fn fold_item_mod(&mut self, node: syn::ItemMod) -> syn::ItemMod {
+        let ident = node.ident.clone();
+        self.named(&ident, |this| syn::fold::fold_item_mod(this, node))
+    }
+
+    fn fold_item_impl(&mut self, node: syn::ItemImpl) -> syn::ItemImpl {
+        let owner = match &*node.self_ty {
+            syn::Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string()),
+            _ => None,
+        }
+        .unwrap_or_else(|| node.self_ty.to_token_stream().to_string());
+        self.owned_by(owner, |this| syn::fold::fold_item_impl(this, node))
+    }
+
+    fn fold_item_trait(&mut self, node: syn::ItemTrait) -> syn::ItemTrait {
+        self.owned_by(node.ident.to_string(), |this| {
+            syn::fold::fold_item_trait(this, node)
+        })
+    }
+
+    fn fold_impl_item_method(&mut self, node: syn::ImplItemMethod) -> syn::ImplItemMethod {
+        let sig = node.sig.clone();
+        self.method_reported(&sig.ident, |this| {
+            let mut node =
+                this.fn_scoped(&sig, |this| syn::fold::fold_impl_item_method(this, node));
+            let rebinds = this.rebind_params(&mut node.sig);
+            node.block = prepend(rebinds, node.block);
+            node
+        })
+    }
+
+    fn fold_trait_item_method(&mut self, node: syn::TraitItemMethod) -> syn::TraitItemMethod {
+        let sig = node.sig.clone();
+        self.method_reported(&sig.ident, |this| {
+            let mut node =
+                this.fn_scoped(&sig, |this| syn::fold::fold_trait_item_method(this, node));
+            if let Some(block) = node.default {
+                let rebinds = this.rebind_params(&mut node.sig);
+                node.default = Some(prepend(rebinds, block));
+            }
+            node
+        })
+    }
+
+    // Constant expressions can't call `borrow_unchecked()`, and there's no
+    // borrow checking to suppress in them anyway.
+
+    fn fold_item_const(&mut self, node: syn::ItemConst) -> syn::ItemConst {
+        node
+    }
+
+    fn fold_item_static(&mut self, node: syn::ItemStatic) -> syn::ItemStatic {
+        node
+    }
+
+    fn fold_impl_item_const(&mut self, node: syn::ImplItemConst) -> syn::ImplItemConst {
+        node
+    }
+
+    fn fold_trait_item_const(&mut self, node: syn::TraitItemConst) -> syn::TraitItemConst {
+        node
+    }
+}
+
+/// Removes any `#[keep_the_borrow_checker]` markers from a node's attributes,
+/// returning whether there were any. Marked nodes are left as they are.
+fn keeps_the_borrow_checker(node: &mut impl Attributed) -> bool {
+    take_marker(node, "keep_the_borrow_checker")
+}
+
+/// Removes any `#[unchecked]` markers from a node's attributes, returning
+/// whether there were any. With `marked_only`, only references in marked nodes
+/// are suppressed.
+fn is_marked_unchecked(node: &mut impl Attributed) -> bool {
+    take_marker(node, "unchecked")
+}
+
+/// Removes any of one of our marker attributes from a node's attributes,
+/// returning whether there were any. Since the markers are removed, they don't
+/// need to be valid where they were.
+fn take_marker(node: &mut impl Attributed, name: &str) -> bool {
+    let attrs = match node.attrs_mut() {
+        Some(attrs) => attrs,
+        None => return false,
+    };
+    let len = attrs.len();
+    attrs.retain(|attr| !is_marker(attr, name));
+    attrs.len() != len
+}
+
+fn is_marker(attr: &syn::Attribute, name: &str) -> bool {
+    is_marker_path(&attr.path, name)
+}
+
+/// Whether an attribute's path names one of our markers, as `you_can::name`,
+/// or just `name` if it's been imported. We leave attributes with the same
+/// name from other crates alone.
+fn is_marker_path(path: &syn::Path, name: &str) -> bool {
+    let mut segments = path.segments.iter().map(|segment| &segment.ident);
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(marker), None, None) => path.leading_colon.is_none() && marker == name,
+        (Some(krate), Some(marker), None) => krate == "you_can" && marker == name,
+        _ => false,
+    }
+}
+
+/// A syntax tree node that may have attributes.
+trait Attributed {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>>;
+}
+
+impl Attributed for Vec<syn::Attribute> {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>> {
+        Some(self)
+    }
+}
+
+impl Attributed for syn::Expr {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>> {
+        use syn::Expr::*;
+        Some(match self {
+            Array(node) => &mut node.attrs,
+            Assign(node) => &mut node.attrs,
+            AssignOp(node) => &mut node.attrs,
+            Async(node) => &mut node.attrs,
+            Await(node) => &mut node.attrs,
+            Binary(node) => &mut node.attrs,
+            Block(node) => &mut node.attrs,
+            Box(node) => &mut node.attrs,
+            Break(node) => &mut node.attrs,
+            Call(node) => &mut node.attrs,
+            Cast(node) => &mut node.attrs,
+            Closure(node) => &mut node.attrs,
+            Continue(node) => &mut node.attrs,
+            Field(node) => &mut node.attrs,
+            ForLoop(node) => &mut node.attrs,
+            Group(node) => &mut node.attrs,
+            If(node) => &mut node.attrs,
+            Index(node) => &mut node.attrs,
+            Let(node) => &mut node.attrs,
+            Lit(node) => &mut node.attrs,
+            Loop(node) => &mut node.attrs,
+            Macro(node) => &mut node.attrs,
+            Match(node) => &mut node.attrs,
+            MethodCall(node) => &mut node.attrs,
+            Paren(node) => &mut node.attrs,
+            Path(node) => &mut node.attrs,
+            Range(node) => &mut node.attrs,
+            Reference(node) => &mut node.attrs,
+            Repeat(node) => &mut node.attrs,
+            Return(node) => &mut node.attrs,
+            Struct(node) => &mut node.attrs,
+            Try(node) => &mut node.attrs,
+            TryBlock(node) => &mut node.attrs,
+            Tuple(node) => &mut node.attrs,
+            Type(node) => &mut node.attrs,
+            Unary(node) => &mut node.attrs,
+            Unsafe(node) => &mut node.attrs,
+            While(node) => &mut node.attrs,
+            Yield(node) => &mut node.attrs,
+            _ => return None,
+        })
+    }
+}
+
+impl Attributed for syn::Pat {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>> {
+        use syn::Pat::*;
+        Some(match self {
+            Box(node) => &mut node.attrs,
+            Ident(node) => &mut node.attrs,
+            Lit(node) => &mut node.attrs,
+            Macro(node) => &mut node.attrs,
+            Or(node) => &mut node.attrs,
+            Path(node) => &mut node.attrs,
+            Range(node) => &mut node.attrs,
+            Reference(node) => &mut node.attrs,
+            Rest(node) => &mut node.attrs,
+            Slice(node) => &mut node.attrs,
+            Struct(node) => &mut node.attrs,
+            Tuple(node) => &mut node.attrs,
+            TupleStruct(node) => &mut node.attrs,
+            Type(node) => &mut node.attrs,
+            Wild(node) => &mut node.attrs,
+            _ => return None,
+        })
+    }
+}
+
+impl Attributed for syn::Item {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>> {
+        use syn::Item::*;
+        Some(match self {
+            Const(node) => &mut node.attrs,
+            Enum(node) => &mut node.attrs,
+            ExternCrate(node) => &mut node.attrs,
+            Fn(node) => &mut node.attrs,
+            ForeignMod(node) => &mut node.attrs,
+            Impl(node) => &mut node.attrs,
+            Macro(node) => &mut node.attrs,
+            Macro2(node) => &mut node.attrs,
+            Mod(node) => &mut node.attrs,
+            Static(node) => &mut node.attrs,
+            Struct(node) => &mut node.attrs,
+            Trait(node) => &mut node.attrs,
+            TraitAlias(node) => &mut node.attrs,
+            Type(node) => &mut node.attrs,
+            Union(node) => &mut node.attrs,
+            Use(node) => &mut node.attrs,
+            _ => return None,
+        })
+    }
+}
+
+impl Attributed for syn::ImplItem {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>> {
+        use syn::ImplItem::*;
+        Some(match self {
+            Const(node) => &mut node.attrs,
+            Method(node) => &mut node.attrs,
+            Type(node) => &mut node.attrs,
+            Macro(node) => &mut node.attrs,
+            _ => return None,
+        })
+    }
+}
+
+impl Attributed for syn::TraitItem {
+    fn attrs_mut(&mut self) -> Option<&mut Vec<syn::Attribute>> {
+        use syn::TraitItem::*;
+        Some(match self {
+            Const(node) => &mut node.attrs,
+            Method(node) => &mut node.attrs,
+            Type(node) => &mut node.attrs,
+            Macro(node) => &mut node.attrs,
+            _ => return None,
+        })
+    }
+}
+
+/// Whether a method conventionally takes `self` by value.
+fn is_consuming(method: &syn::Ident) -> bool {
+    let method = method.to_string();
+    method.starts_with("into")
+        || method.starts_with("unwrap")
+        || CONSUMING_METHODS.contains(&method.as_str())
+}
+
+/// If an expression evaluates to the result of a call to a method returning a
+/// borrow guard or iterator, or of `Pin::new()`, returns the expression it
+/// borrows from, and whether it's borrowed mutably (if we know). This looks
+/// through `unwrap()`, `expect()`, and `?`.
+fn borrowing_call(expr: &syn::Expr) -> Option<(&syn::Expr, Option<bool>)> {
+    match expr {
+        syn::Expr::MethodCall(call)
+            if BORROWING_METHODS.contains(&call.method.to_string().as_str()) =>
+        {
+            let method = call.method.to_string();
+            let mutable = is_mutating(&call.method) || LOCKING_METHODS.contains(&method.as_str());
+            Some((&call.receiver, Some(mutable)))
+        },
+        syn::Expr::Call(call) if is_pin_new(&call.func) && call.args.len() == 1 =>
+            match &call.args[0] {
+                syn::Expr::Reference(reference) =>
+                    Some((&reference.expr, Some(reference.mutability.is_some()))),
+                pointer => Some((pointer, None)),
+            },
+        syn::Expr::MethodCall(call) if call.method == "unwrap" || call.method == "expect" =>
+            borrowing_call(&call.receiver),
+        syn::Expr::Try(syn::ExprTry { expr, .. })
+        | syn::Expr::Paren(syn::ExprParen { expr, .. }) => borrowing_call(expr),
+        _ => None,
+    }
+}
+
+/// Whether an expression is a path to `Pin::new()` or `Pin::new_unchecked()`.
+fn is_pin_new(expr: &syn::Expr) -> bool {
+    let segments = match expr {
+        syn::Expr::Path(path) if path.qself.is_none() => &path.path.segments,
+        _ => return false,
+    };
+    let mut names = segments.iter().rev().map(|segment| &segment.ident);
+    matches!(
+        (names.next(), names.next()),
+        (Some(function), Some(pin)) if pin == "Pin" && (function == "new" || function == "new_unchecked")
+    )
+}
+
+/// Whether an expression is a call to the `pin!()` macro.
+fn is_pin_macro(expr: &syn::Expr) -> bool {
+    matches!(expr, syn::Expr::Macro(node) if node.mac.path.segments.last().is_some_and(|segment| segment.ident == "pin"))
+}
+
+/// The kind of a reference expression.
+fn reference_kind(node: &syn::ExprReference) -> ReferenceKind {
+    if node.mutability.is_some() {
+        ReferenceKind::Mut
+    } else {
+        ReferenceKind::Shared
+    }
+}
+
+/// Whether a method is known to take `self` by reference, so that we can give
+/// it a place behind an unbounded reference in place of its receiver. We
+/// can't tell how other methods take `self`, since they may be user-defined,
+/// so we only trust the names of the common methods that borrow from or
+/// inspect their receiver. Builders often take `self` by value in methods
+/// named like mutating ones, such as `push()` or `set_name()`.
+fn takes_self_by_reference(method: &syn::Ident) -> bool {
+    let name = method.to_string();
+    !AMBIGUOUS_METHODS.contains(&name.as_str())
+        && (BORROWING_METHODS.contains(&name.as_str())
+            || INSPECTING_METHODS.contains(&name.as_str()))
+}
+
+/// Whether a method of a standard library collection takes `self` by
+/// reference, which we can tell from its name alone.
+fn collection_takes_self_by_reference(method: &syn::Ident) -> bool {
+    !is_consuming(method)
+        && !AMBIGUOUS_METHODS.contains(&method.to_string().as_str())
+        && (is_mutating(method) || takes_self_by_reference(method))
+}
+
+/// Whether a type is one of the standard library collections.
+fn is_collection_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) if path.qself.is_none() =>
+            path.path.segments.last().is_some_and(|segment| {
+                COLLECTION_TYPES.contains(&segment.ident.to_string().as_str())
+            }),
+        _ => false,
+    }
+}
+
+/// Whether an expression evaluates to one of the standard library
+/// collections, like `vec![]`, `String::new()`, or `HashMap::from(...)`.
+fn is_collection(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Macro(node) => node
+            .mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "vec" || segment.ident == "format"),
+        syn::Expr::Call(call) => match &*call.func {
+            syn::Expr::Path(path) if path.qself.is_none() => {
+                let segments = &path.path.segments;
+                segments.len() >= 2
+                    && COLLECTION_TYPES
+                        .contains(&segments[segments.len() - 2].ident.to_string().as_str())
+            },
+            _ => false,
+        },
+        syn::Expr::MethodCall(call) if call.method == "to_string" => true,
+        syn::Expr::MethodCall(call) if call.method == "collect" => call
+            .turbofish
+            .as_ref()
+            .and_then(|turbofish| turbofish.args.first())
+            .is_some_and(
+                |arg| matches!(arg, syn::GenericMethodArgument::Type(ty) if is_collection_type(ty)),
+            ),
+        syn::Expr::Paren(node) => is_collection(&node.expr),
+        _ => false,
+    }
+}
+
+/// Whether a method conventionally takes `self` by mutable reference.
+fn is_mutating(method: &syn::Ident) -> bool {
+    let method = method.to_string();
+    method.ends_with("_mut")
+        || method.starts_with("as_mut")
+        || method.starts_with("set_")
+        || MUTATING_METHODS.contains(&method.as_str())
+}
+
+/// Whether an expression is a place expression, which can be borrowed without
+/// creating a temporary.
+fn is_place(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Path(_)
+        | syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Deref(_),
+            ..
+        }) => true,
+        syn::Expr::Field(syn::ExprField { base: expr, .. })
+        | syn::Expr::Index(syn::ExprIndex { expr, .. })
+        | syn::Expr::Paren(syn::ExprParen { expr, .. }) => is_place(expr),
+        _ => false,
+    }
+}
+
+/// Returns the local variable that a place expression is in, if any.
+fn place_root(expr: &syn::Expr) -> Option<&syn::Ident> {
+    match expr {
+        syn::Expr::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Deref(_),
+            expr,
+            ..
+        })
+        | syn::Expr::Field(syn::ExprField { base: expr, .. })
+        | syn::Expr::Index(syn::ExprIndex { expr, .. })
+        | syn::Expr::Paren(syn::ExprParen { expr, .. }) => place_root(expr),
+        _ => None,
+    }
+}
+
+/// Returns the local variable that a place expression is in, the fields of it
+/// that the place is in (like `.a.b`), and whether the place is somewhere
+/// else inside of those fields (behind an index or a dereference).
+fn place_parts(expr: &syn::Expr) -> Option<(&syn::Ident, String, bool)> {
+    match expr {
+        syn::Expr::Path(path) if path.qself.is_none() => path
+            .path
+            .get_ident()
+            .map(|root| (root, String::new(), false)),
+        syn::Expr::Field(field) => {
+            let (root, mut fields, projected) = place_parts(&field.base)?;
+            if !projected {
+                match &field.member {
+                    syn::Member::Named(name) => fields += &format!(".{name}"),
+                    syn::Member::Unnamed(index) => fields += &format!(".{}", index.index),
+                }
+            }
+            Some((root, fields, projected))
+        },
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Deref(_),
+            expr,
+            ..
+        })
+        | syn::Expr::Index(syn::ExprIndex { expr, .. }) => {
+            let (root, fields, _) = place_parts(expr)?;
+            Some((root, fields, true))
+        },
+        syn::Expr::Paren(syn::ExprParen { expr, .. }) => place_parts(expr),
+        _ => None,
+    }
+}
+
+/// Inserts statements at the beginning of a block.
+fn prepend(mut stmts: Vec<syn::Stmt>, block: syn::Block) -> syn::Block {
+    stmts.extend(block.stmts);
+    syn::Block {
+        brace_token: block.brace_token,
+        stmts,
+    }
+}
+
+/// A `let ... else { ... };` statement, which `syn` only gives us as verbatim
+/// tokens.
+struct LocalElse {
+    attrs: Vec<syn::Attribute>,
+    let_token: syn::Token![let],
+    pat: syn::Pat,
+    eq_token: syn::Token![=],
+    init: syn::Expr,
+    else_token: syn::Token![else],
+    diverge: syn::Block,
+}
+
+impl syn::parse::Parse for LocalElse {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let let_token = input.parse()?;
+        let mut pat = input.parse()?;
+        if input.peek(syn::Token![:]) {
+            pat = syn::Pat::Type(syn::PatType {
+                attrs: vec![],
+                pat: Box::new(pat),
+                colon_token: input.parse()?,
+                ty: input.parse()?,
+            });
+        }
+        Ok(LocalElse {
+            attrs,
+            let_token,
+            pat,
+            eq_token: input.parse()?,
+            init: input.parse()?,
+            else_token: input.parse()?,
+            diverge: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for LocalElse {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.append_all(&self.attrs);
+        self.let_token.to_tokens(tokens);
+        self.pat.to_tokens(tokens);
+        self.eq_token.to_tokens(tokens);
+        self.init.to_tokens(tokens);
+        self.else_token.to_tokens(tokens);
+        self.diverge.to_tokens(tokens);
+    }
+}
+
+/// The arguments of one of the well-known macros from the standard library,
+/// which `syn` only gives us as tokens. Variables captured implicitly by a
+/// format string are made into explicit named arguments, so that we can treat
+/// them like any other.
+struct MacroArgs {
+    args: syn::punctuated::Punctuated<MacroArg, syn::Token![,]>,
+    /// The length of a `vec![value; len]`.
+    repeat: Option<(syn::Token![;], syn::Expr)>,
+    /// The original condition of an `assert!()` without a message, and its
+    /// tokens, which its panic message includes, in case we change it.
+    assertion: Option<(syn::Expr, proc_macro2::TokenStream)>,
+}
+
+struct MacroArg {
+    name: Option<(syn::Ident, syn::Token![=])>,
+    expr: syn::Expr,
+    usage: MacroArgUsage,
+}
+
+/// How a macro uses one of its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MacroArgUsage {
+    /// Evaluated as an expression of its own.
+    Value,
+    /// Borrowed, as the arguments of `format_args!` are.
+    Borrowed,
+    /// Used as the receiver of a method call, as the first argument of
+    /// `write!` is.
+    Receiver,
+}
+
+impl MacroArgs {
+    /// Parses the arguments of a macro, if it's one that we know about and
+    /// its arguments are all expressions.
+    fn parse(node: &syn::Macro) -> Option<Self> {
+        let name = node.path.segments.last()?.ident.to_string();
+        let format_string = match name.as_str() {
+            "format" | "format_args" | "print" | "println" | "eprint" | "eprintln" | "panic"
+            | "todo" | "unimplemented" | "unreachable" => Some(0),
+            "write" | "writeln" | "assert" | "debug_assert" => Some(1),
+            "assert_eq" | "assert_ne" | "debug_assert_eq" | "debug_assert_ne" => Some(2),
+            "vec" | "pin" => None,
+            _ => return None,
+        };
+
+        let mut args: MacroArgs = syn::parse::Parser::parse2(
+            |input: syn::parse::ParseStream| {
+                let mut args = syn::punctuated::Punctuated::new();
+                let mut repeat = None;
+                while !input.is_empty() {
+                    let arg_name = if input.peek(syn::Ident)
+                        && input.peek2(syn::Token![=])
+                        && !input.peek2(syn::Token![==])
+                    {
+                        Some((input.parse()?, input.parse()?))
+                    } else {
+                        None
+                    };
+                    args.push_value(MacroArg {
+                        name: arg_name,
+                        expr: input.parse()?,
+                        usage: MacroArgUsage::Value,
+                    });
+                    if name == "vec" && args.len() == 1 && input.peek(syn::Token![;]) {
+                        repeat = Some((input.parse()?, input.parse()?));
+                        break;
+                    }
+                    if input.is_empty() {
+                        break;
+                    }
+                    args.push_punct(input.parse()?);
+                }
+                Ok(MacroArgs {
+                    args,
+                    repeat,
+                    assertion: None,
+                })
+            },
+            node.tokens.clone(),
+        )
+        .ok()?;
+
+        let format_string = match format_string {
+            Some(format_string) => format_string,
+            None => return Some(args),
+        };
+        if (name == "assert" || name == "debug_assert")
+            && args.args.len() == 1
+            && !args.args.trailing_punct()
+        {
+            args.assertion = Some((args.args[0].expr.clone(), node.tokens.clone()));
+        }
+        for (index, arg) in args.args.iter_mut().enumerate() {
+            arg.usage = match (name.as_str(), index) {
+                ("write" | "writeln", 0) => MacroArgUsage::Receiver,
+                ("assert" | "debug_assert", 0) => MacroArgUsage::Value,
+                (_, index) if index != format_string => MacroArgUsage::Borrowed,
+                _ => MacroArgUsage::Value,
+            };
+        }
+
+        let captures: Vec<syn::Ident> =
+            match args.args.iter().nth(format_string).map(|arg| &arg.expr) {
+                Some(syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(literal),
+                    ..
+                })) => format_string_captures(&literal.value())
+                    .iter()
+                    .map(|captured| syn::Ident::new(captured, literal.span()))
+                    .collect(),
+                _ => vec![],
+            };
+        for ident in captures {
+            let named = args
+                .args
+                .iter()
+                .any(|arg| arg.name.as_ref().is_some_and(|(name, _)| *name == ident));
+            if !named {
+                args.args.push(MacroArg {
+                    name: Some((ident.clone(), Default::default())),
+                    expr: parse_quote_spanned! { ident.span() => #ident },
+                    usage: MacroArgUsage::Borrowed,
+                });
+            }
+        }
+        Some(args)
+    }
+}
+
+impl ToTokens for MacroArgs {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.args.to_tokens(tokens);
+        if let Some((semi_token, len)) = &self.repeat {
+            semi_token.to_tokens(tokens);
+            len.to_tokens(tokens);
+        }
+        if let Some((condition, original)) = &self.assertion {
+            if self.args[0].expr.to_token_stream().to_string()
+                != condition.to_token_stream().to_string()
+            {
+                // Keep the message from mentioning our unbounded references.
+                tokens.extend(quote_spanned! { condition.span() =>
+                    , "assertion failed: {}", ::core::stringify!(#original)
+                });
+            }
+        }
+    }
+}
+
+impl ToTokens for MacroArg {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        if let Some((name, eq_token)) = &self.name {
+            name.to_tokens(tokens);
+            eq_token.to_tokens(tokens);
+        }
+        self.expr.to_tokens(tokens);
+    }
+}
+
+/// Returns the names of the variables that a format string captures
+/// implicitly, like the `x` in `"{x}"` or `"{x:?}"`.
+fn format_string_captures(format_string: &str) -> Vec<String> {
+    let mut captures: Vec<String> = vec![];
+    let mut chars = format_string.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            },
+            '{' => {
+                let mut name = String::new();
+                while let Some(&char) = chars.peek().filter(|c| **c != '}' && **c != ':') {
+                    name.push(char);
+                    chars.next();
+                }
+                let is_ident = name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                    && name != "_";
+                if is_ident && !captures.contains(&name) {
+                    captures.push(name);
+                }
+            },
+            _ => {},
+        }
+    }
+    captures
+}
+
+/// What we know about a value being matched against a pattern.
+#[derive(Debug, Default)]
+struct Matched {
+    /// Whether the value is known to be a reference, and if so, whether it's
+    /// mutable.
+    reference: Option<bool>,
+    /// The local variable that the value is (or refers to) a place in, if any.
+    root: Option<syn::Ident>,
+}
+
+/// The statements we've hoisted out of an expression in an extending position.
+#[derive(Default)]
+struct Hoisted {
+    /// The statements that go right before the expression.
+    stmts: Vec<syn::Stmt>,
+    /// The declarations of the temporaries we've hoisted out of the tail of a
+    /// block (or a branch of an `if` or `match`), which go before the whole
+    /// `let` statement, since a `let` statement in the block would drop them
+    /// at its end. We only assign them in the block itself.
+    declarations: Vec<syn::Stmt>,
+    /// Whether the expression is in the tail of a block.
+    in_tail: bool,
+}
+
+/// The arguments of `#[turn_off_the_borrow_checker(...)]`, which restrict the
+/// references it suppresses. The default suppresses all of them.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// `shared_only`: only suppress shared references.
+    pub shared_only: bool,
+    /// `mut_only`: only suppress mutable references.
+    pub mut_only: bool,
+    /// `only = "x, y"`: only suppress references to places in these local
+    /// variables.
+    pub only: Option<Vec<syn::Ident>>,
+    /// `except = "x, y"`: don't suppress references to places in these local
+    /// variables.
+    pub except: Vec<syn::Ident>,
+    /// `marked_only`: only suppress references in code marked with
+    /// `#[unchecked]`.
+    pub marked_only: bool,
+    /// `checked`: track the references we suppress at runtime, panicking
+    /// when one is used after a conflicting access.
+    pub checked: bool,
+}
+
+impl syn::parse::Parse for Options {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut options = Options::default();
+        let mut seen: Vec<String> = vec![];
+        let args =
+            syn::punctuated::Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated(
+                input,
+            )?;
+        for arg in args {
+            let name = match &arg {
+                syn::NestedMeta::Meta(meta) => meta.path().get_ident().map(ToString::to_string),
+                syn::NestedMeta::Lit(_) => None,
+            };
+            match (name.as_deref(), &arg) {
+                (Some("shared_only"), syn::NestedMeta::Meta(syn::Meta::Path(_))) =>
+                    options.shared_only = true,
+                (Some("mut_only"), syn::NestedMeta::Meta(syn::Meta::Path(_))) =>
+                    options.mut_only = true,
+                (Some("marked_only"), syn::NestedMeta::Meta(syn::Meta::Path(_))) =>
+                    options.marked_only = true,
+                (Some("checked"), syn::NestedMeta::Meta(syn::Meta::Path(_))) =>
+                    options.checked = true,
+                (
+                    Some("only"),
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                        lit: syn::Lit::Str(names),
+                        ..
+                    })),
+                ) => options.only = Some(parse_names(names)?),
+                (
+                    Some("except"),
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                        lit: syn::Lit::Str(names),
+                        ..
+                    })),
+                ) => options.except = parse_names(names)?,
+                (Some(name @ ("shared_only" | "mut_only" | "marked_only" | "checked")), _) =>
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        format!("`{name}` doesn't take a value"),
+                    )),
+                (Some(name @ ("only" | "except")), _) =>
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        format!(
+                            "expected `{name} = \"...\"`, with a comma-separated list of variable \
+                             names"
+                        ),
+                    )),
+                _ =>
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "unknown argument, expected `shared_only`, `mut_only`, `only = \"...\"`, \
+                         `except = \"...\"`, `marked_only`, or `checked`",
+                    )),
+            }
+
+            let name = name.expect("known arguments have names");
+            let conflicting = match name.as_str() {
+                "shared_only" => Some("mut_only"),
+                "mut_only" => Some("shared_only"),
+                "only" => Some("except"),
+                "except" => Some("only"),
+                _ => None,
+            };
+            if seen.contains(&name) {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    format!("`{name}` is specified more than once"),
+                ));
+            } else if let Some(conflicting) =
+                conflicting.filter(|conflicting| seen.iter().any(|seen| seen == conflicting))
+            {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    format!("`{name}` can't be used with `{conflicting}`"),
+                ));
+            }
+            seen.push(name);
+        }
+        Ok(options)
+    }
+}
+
+/// Parses a comma-separated list of variable names from a string literal.
+fn parse_names(names: &syn::LitStr) -> syn::Result<Vec<syn::Ident>> {
+    names
+        .value()
+        .split(',')
+        .map(str::trim)
+        .map(|name| {
+            syn::parse::Parser::parse_str(syn::ext::IdentExt::parse_any, name)
+                .map(|ident: syn::Ident| syn::Ident::new(&ident.to_string(), names.span()))
+                .map_err(|_| {
+                    syn::Error::new(
+                        names.span(),
+                        format!("expected a variable name, found `{name}`"),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// How a local binding was declared, which determines how we can reborrow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Binding {
+    /// `mut x`, which we can borrow mutably.
+    Mutable,
+    /// `mut x` holding a standard library collection, like a `Vec` or a
+    /// `String`, whose methods we know how to borrow it for.
+    MutableCollection,
+    /// `ref mut x`, `x: &mut T`, `&mut self`, or `x = &mut ...`.
+    MutReference,
+    /// `ref x`, `x: &T`, `&self`, or `x = &...`.
+    SharedReference,
+    /// Anything else, which we leave alone.
+    Unknown,
+}
+
+impl Binding {
+    /// Whether this is a `mut` binding of an owned value.
+    fn is_mutable(self) -> bool {
+        matches!(self, Binding::Mutable | Binding::MutableCollection)
+    }
+}
+
+#[derive(Debug, Default)]
+struct BindingCollector {
+    bindings: Vec<(syn::Ident, Binding)>,
+}
+
+impl<'ast> Visit<'ast> for BindingCollector {
+    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
+        self.bindings.push(
+            (node.ident.clone(), match (&node.by_ref, &node.mutability) {
+                (Some(_), Some(_)) => Binding::MutReference,
+                (Some(_), None) => Binding::SharedReference,
+                (None, Some(_)) => Binding::Mutable,
+                (None, None) => Binding::Unknown,
+            }),
+        );
+        syn::visit::visit_pat_ident(self, node);
+    }
+
+    fn visit_pat_type(&mut self, node: &'ast syn::PatType) {
+        match (&*node.pat, &*node.ty) {
+            (
+                syn::Pat::Ident(syn::PatIdent {
+                    by_ref: None,
+                    mutability: None,
+                    subpat: None,
+                    ident,
+                    ..
+                }),
+                syn::Type::Reference(reference),
+            ) => self.bindings.push((
+                ident.clone(),
+                if reference.mutability.is_some() {
+                    Binding::MutReference
+                } else {
+                    Binding::SharedReference
+                },
+            )),
+            _ => syn::visit::visit_pat_type(self, node),
+        }
+    }
+
+    fn visit_expr(&mut self, _node: &'ast syn::Expr) {
+        // Expressions in patterns (like ranges and constants) don't bind.
+    }
+}
+
+#[derive(Debug, Default)]
+struct RefCollector {
+    /// Each reference binding, whether it's mutable, and its span.
+    refs: Vec<(syn::Ident, bool, Span)>,
+    /// Whether the value being matched is known to be a reference, and if so,
+    /// whether it's mutable.
+    matching_reference: Option<bool>,
+    /// Whether the default binding mode is currently by-reference, and if so,
+    /// whether it's mutable.
+    by_reference: Option<bool>,
+}
+
+impl RefCollector {
+    /// Matching a reference against a non-reference pattern switches the
+    /// default binding mode to by-reference.
+    fn destructure(&mut self) {
+        if self.matching_reference.is_some() {
+            self.by_reference = self.matching_reference;
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for RefCollector {
+    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
+        // By convention, capitalized identifiers in patterns are unit structs,
+        // unit variants, or constants, not bindings. A `mut` binding resets
+        // the binding mode to by-value.
+        let is_binding = !node.ident.to_string().starts_with(char::is_uppercase);
+        let mutable = match (&node.by_ref, self.by_reference) {
+            (Some(_), _) => Some(node.mutability.is_some()),
+            (None, Some(mutable)) if is_binding && node.mutability.is_none() => Some(mutable),
+            _ => None,
+        };
+        if let Some(mutable) = mutable {
+            self.refs.push((node.ident.clone(), mutable, node.span()));
+        }
+        syn::visit::visit_pat_ident(self, node);
+    }
+
+    fn visit_pat_reference(&mut self, node: &'ast syn::PatReference) {
+        // A `&` pattern resets the binding mode to by-value, and we don't know
+        // whether the value it's matching is another reference.
+        let matching_reference = self.matching_reference.take();
+        let by_reference = self.by_reference.take();
+        syn::visit::visit_pat_reference(self, node);
+        self.matching_reference = matching_reference;
+        self.by_reference = by_reference;
+    }
+
+    fn visit_pat_slice(&mut self, node: &'ast syn::PatSlice) {
+        self.destructure();
+        syn::visit::visit_pat_slice(self, node);
+    }
+
+    fn visit_pat_struct(&mut self, node: &'ast syn::PatStruct) {
+        self.destructure();
+        syn::visit::visit_pat_struct(self, node);
+    }
+
+    fn visit_pat_tuple(&mut self, node: &'ast syn::PatTuple) {
+        self.destructure();
+        syn::visit::visit_pat_tuple(self, node);
+    }
+
+    fn visit_pat_tuple_struct(&mut self, node: &'ast syn::PatTupleStruct) {
+        self.destructure();
+        syn::visit::visit_pat_tuple_struct(self, node);
+    }
+
+    fn visit_expr(&mut self, _node: &'ast syn::Expr) {
+        // Expressions in patterns (like ranges and constants) don't bind.
+    }
+}
+
+/// Collects the uses of identifiers in a closure that might be captured local
+/// variables, along with whether each use is only by reference.
+#[derive(Debug, Default)]
+struct CaptureCollector {
+    uses: Vec<(syn::Ident, CaptureUse)>,
+    /// The names bound anywhere inside of the closure.
+    bound: Vec<syn::Ident>,
+    /// Whether the closure contains tokens we can't see into, like macros.
+    opaque: bool,
+    /// How the expression being visited is used.
+    usage: CaptureUse,
+}
+
+/// How a closure or async block uses a variable that it captures.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum CaptureUse {
+    /// Its value is used, so it might be moved.
+    #[default]
+    Value,
+    /// It's the receiver of a method that takes `self` by reference if it's a
+    /// standard library collection.
+    CollectionReceiver,
+    /// It's only used by reference.
+    Reference,
+}
+
+impl CaptureCollector {
+    fn visit_use(&mut self, node: &syn::Expr, usage: CaptureUse) {
+        self.usage = usage;
+        self.visit_expr(node);
+    }
+}
+
+impl<'ast> Visit<'ast> for CaptureCollector {
+    fn visit_expr(&mut self, node: &'ast syn::Expr) {
+        let usage = std::mem::take(&mut self.usage);
+        match node {
+            syn::Expr::Path(path) if path.qself.is_none() =>
+                if let Some(ident) = path.path.get_ident() {
+                    self.uses.push((ident.clone(), usage));
+                },
+            syn::Expr::Paren(node) => self.visit_use(&node.expr, usage),
+            syn::Expr::Field(node) => self.visit_use(&node.base, usage),
+            syn::Expr::Unary(node) if matches!(node.op, syn::UnOp::Deref(_)) =>
+                self.visit_use(&node.expr, usage),
+            syn::Expr::Reference(node) => self.visit_use(&node.expr, CaptureUse::Reference),
+            syn::Expr::Index(node) => {
+                self.visit_use(&node.expr, CaptureUse::Reference);
+                self.visit_expr(&node.index);
+            },
+            syn::Expr::Assign(node) => {
+                self.visit_use(&node.left, CaptureUse::Reference);
+                self.visit_expr(&node.right);
+            },
+            syn::Expr::AssignOp(node) => {
+                self.visit_use(&node.left, CaptureUse::Reference);
+                self.visit_expr(&node.right);
+            },
+            syn::Expr::MethodCall(node) => {
+                let usage = if takes_self_by_reference(&node.method) {
+                    CaptureUse::Reference
+                } else if collection_takes_self_by_reference(&node.method) {
+                    CaptureUse::CollectionReceiver
+                } else {
+                    CaptureUse::Value
+                };
+                self.visit_use(&node.receiver, usage);
+                for arg in &node.args {
+                    self.visit_expr(arg);
+                }
+            },
+            syn::Expr::Verbatim(_) => self.opaque = true,
+            _ => syn::visit::visit_expr(self, node),
+        }
+    }
+
+    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
+        self.bound.push(node.ident.clone());
+        syn::visit::visit_pat_ident(self, node);
+    }
+
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        let args = match MacroArgs::parse(node) {
+            Some(args) => args,
+            None => return self.opaque = true,
+        };
+        for arg in &args.args {
+            let usage = if arg.usage == MacroArgUsage::Value {
+                CaptureUse::Value
+            } else {
+                CaptureUse::Reference
+            };
+            self.visit_use(&arg.expr, usage);
+        }
+        if let Some((_, len)) = &args.repeat {
+            self.visit_expr(len);
+        }
+    }
+
+    fn visit_attribute(&mut self, node: &'ast syn::Attribute) {
+        // We shouldn't replace the captures used by code that's kept borrow
+        // checked, so we leave the whole closure alone.
+        if is_marker(node, "keep_the_borrow_checker") {
+            self.opaque = true;
+        }
+    }
+
+    fn visit_item(&mut self, node: &'ast syn::Item) {
+        // Nested items can't capture anything, but macros might expand to
+        // statements that do.
+        if let syn::Item::Macro(_) = node {
+            self.opaque = true;
+        }
+    }
+}
+
+/// Collects the places in local variables that a statement accesses, outside
+/// of any nested blocks (which we check separately) and closures (which we
+/// don't check).
+#[derive(Default)]
+struct AccessCollector {
+    accesses: Vec<PlaceAccess>,
+    /// How the expression being visited is used.
+    usage: PlaceUse,
+}
+
+struct PlaceAccess {
+    root: syn::Ident,
+    /// The fields of the variable that the place is in, like `.a.b`.
+    fields: String,
+    /// Whether the place is somewhere else inside of those fields.
+    projected: bool,
+    usage: PlaceUse,
+    place: syn::Expr,
+    span: proc_macro2::Span,
+}
+
+/// How a place expression is used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum PlaceUse {
+    /// Its value is used, so it's copied or moved.
+    #[default]
+    Value,
+    /// It's borrowed immutably, or read without being moved.
+    Read,
+    /// It's borrowed mutably, or assigned to.
+    Write,
+}
+
+impl AccessCollector {
+    fn visit_used(&mut self, node: &syn::Expr, usage: PlaceUse) {
+        self.usage = usage;
+        self.visit_expr(node);
+    }
+}
+
+impl<'ast> Visit<'ast> for AccessCollector {
+    fn visit_expr(&mut self, node: &'ast syn::Expr) {
+        let usage = std::mem::take(&mut self.usage);
+        if let Some((root, fields, projected)) = place_parts(node) {
+            self.accesses.push(PlaceAccess {
+                root: root.clone(),
+                fields,
+                projected,
+                usage,
+                place: node.clone(),
+                span: node.span(),
+            });
+            // The indices in a place are evaluated as expressions of their
+            // own.
+            let mut node = node;
+            loop {
+                node = match node {
+                    syn::Expr::Field(field) => &field.base,
+                    syn::Expr::Unary(unary) => &unary.expr,
+                    syn::Expr::Paren(paren) => &paren.expr,
+                    syn::Expr::Index(index) => {
+                        self.visit_expr(&index.index);
+                        &index.expr
+                    },
+                    _ => break,
+                };
+            }
+            return;
+        }
+
+        match node {
+            syn::Expr::Reference(node) => {
+                let usage = if node.mutability.is_some() {
+                    PlaceUse::Write
+                } else {
+                    PlaceUse::Read
+                };
+                self.visit_used(&node.expr, usage);
+            },
+            syn::Expr::Assign(node) => {
+                self.visit_used(&node.left, PlaceUse::Write);
+                self.visit_expr(&node.right);
+            },
+            syn::Expr::AssignOp(node) => {
+                self.visit_used(&node.left, PlaceUse::Write);
+                self.visit_expr(&node.right);
+            },
+            syn::Expr::MethodCall(node) => {
+                let usage = if is_consuming(&node.method) {
+                    PlaceUse::Value
+                } else if is_mutating(&node.method) {
+                    PlaceUse::Write
+                } else {
+                    PlaceUse::Read
+                };
+                self.visit_used(&node.receiver, usage);
+                for arg in &node.args {
+                    self.visit_expr(arg);
+                }
+            },
+            syn::Expr::Let(node) => self.visit_used(&node.expr, PlaceUse::Read),
+            syn::Expr::Match(node) => self.visit_used(&node.expr, PlaceUse::Read),
+            syn::Expr::If(node) => self.visit_expr(&node.cond),
+            syn::Expr::While(node) => self.visit_expr(&node.cond),
+            syn::Expr::ForLoop(node) => self.visit_expr(&node.expr),
+            syn::Expr::Closure(_)
+            | syn::Expr::Async(_)
+            | syn::Expr::Block(_)
+            | syn::Expr::Loop(_)
+            | syn::Expr::Unsafe(_)
+            | syn::Expr::TryBlock(_) => {},
+            _ => syn::visit::visit_expr(self, node),
+        }
+    }
+
+    fn visit_block(&mut self, _node: &'ast syn::Block) {}
+
+    fn visit_item(&mut self, _node: &'ast syn::Item) {}
+
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        if let Some(args) = MacroArgs::parse(node) {
+            for arg in &args.args {
+                let usage = match arg.usage {
+                    MacroArgUsage::Value => PlaceUse::Value,
+                    MacroArgUsage::Borrowed => PlaceUse::Read,
+                    MacroArgUsage::Receiver => PlaceUse::Write,
+                };
+                self.visit_used(&arg.expr, usage);
+            }
+            if let Some((_, len)) = &args.repeat {
+                self.visit_expr(len);
+            }
+        }
+    }
+}
+
+/// Replaces the captured variables in a closure with the unbounded references
+/// they've been rebound to.
+struct CaptureReplacer {
+    /// Each captured variable, the unbounded reference it's been rebound to,
+    /// and whether that reference needs to be dereferenced to replace it.
+    replacements: Vec<(syn::Ident, syn::Ident, bool)>,
+}
+
+impl Fold for CaptureReplacer {
+    fn fold_expr(&mut self, node: syn::Expr) -> syn::Expr {
+        if let syn::Expr::Path(path) = &node {
+            if let Some(ident) = path.path.get_ident().filter(|_| path.qself.is_none()) {
+                if let Some((_, unbound, deref)) = self
+                    .replacements
+                    .iter()
+                    .find(|(captured, ..)| captured == ident)
+                {
+                    return if *deref {
+                        parse_quote_spanned! { ident.span() => (*#unbound) }
+                    } else {
+                        parse_quote_spanned! { ident.span() => #unbound }
+                    };
+                }
+            }
+        }
+        syn::fold::fold_expr(self, node)
+    }
+
+    fn fold_macro(&mut self, node: syn::Macro) -> syn::Macro {
+        let mut args = match MacroArgs::parse(&node) {
+            Some(args) => args,
+            None => return node,
+        };
+        for arg in args.args.iter_mut() {
+            arg.expr = self.fold_expr(arg.expr.clone());
+        }
+        args.repeat = args
+            .repeat
+            .map(|(semi_token, len)| (semi_token, self.fold_expr(len)));
+        syn::Macro {
+            tokens: args.into_token_stream(),
+            ..node
+        }
+    }
+
+    fn fold_field_value(&mut self, node: syn::FieldValue) -> syn::FieldValue {
+        // Shorthand fields need a colon if their value changed.
+        let mut node = syn::fold::fold_field_value(self, node);
+        node.colon_token = node.colon_token.or_else(|| Some(Default::default()));
+        node
+    }
+
+    fn fold_item(&mut self, node: syn::Item) -> syn::Item {
+        node
+    }
+}
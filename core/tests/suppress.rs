@@ -0,0 +1,249 @@
+use {
+    quote::quote,
+    you_can_core::{Options, ReferenceKind},
+};
+
+#[test]
+fn suppresses_references() {
+    let (output, report) = you_can_core::suppress(
+        quote! {
+            fn main() {
+                let mut owned = vec![1, 32];
+                let mut_1 = &mut owned[0];
+                let shared = &owned[1];
+                drop(owned);
+            }
+        },
+        Options::default(),
+    );
+
+    assert_eq!(report.references.len(), 2);
+    assert!(report.methods.is_empty());
+    assert_eq!(
+        output.to_string().matches("borrow_unchecked").count(),
+        2,
+        "{output}"
+    );
+}
+
+#[test]
+fn respects_options() {
+    let options: Options = syn::parse_str("mut_only").unwrap();
+    assert!(options.mut_only);
+
+    let (output, report) = you_can_core::suppress(
+        quote! {
+            fn main() {
+                let mut owned = vec![1, 32];
+                let mut_1 = &mut owned[0];
+                let shared = &owned[1];
+            }
+        },
+        options,
+    );
+
+    assert_eq!(report.references.len(), 1);
+    assert_eq!(
+        output.to_string().matches("borrow_unchecked").count(),
+        1,
+        "{output}"
+    );
+}
+
+#[test]
+fn reports_methods_separately() {
+    let (_, report) = you_can_core::suppress(
+        quote! {
+            impl Counter {
+                fn first(&mut self) -> &mut u32 {
+                    &mut self.counts[0]
+                }
+            }
+        },
+        Options::default(),
+    );
+
+    assert!(report.references.is_empty());
+    assert_eq!(report.methods.len(), 1);
+    assert_eq!(report.methods[0].0, "Counter::first");
+    assert_eq!(report.methods[0].1.len(), 1);
+}
+
+#[test]
+fn rejects_unsupported_input() {
+    let (output, report) = you_can_core::suppress(quote! { , }, Options::default());
+
+    assert!(output.to_string().contains("compile_error"));
+    assert!(report.references.is_empty());
+}
+
+#[test]
+fn reports_kinds_and_items() {
+    let (_, report) = you_can_core::suppress(
+        quote! {
+            mod lesson {
+                fn main() {
+                    let mut owned = Some(vec![1, 32]);
+                    let shared = &owned;
+                    if let Some(ref mut inner) = owned {
+                        // the receiver is borrowed too
+                        inner.push(3);
+                    }
+                }
+            }
+        },
+        Options::default(),
+    );
+
+    let kinds: Vec<_> = report
+        .references
+        .iter()
+        .map(|reference| reference.kind)
+        .collect();
+    assert_eq!(kinds, [
+        ReferenceKind::Shared,
+        ReferenceKind::Mut,
+        ReferenceKind::RefMut
+    ]);
+    for reference in &report.references {
+        assert_eq!(reference.item.as_deref(), Some("lesson::main"));
+    }
+}
+
+#[test]
+fn strips_markers() {
+    let stripped = you_can_core::strip_markers(quote! {
+        #[you_can::keep_the_borrow_checker]
+        fn main() {
+            #[inline]
+            let first = #[unchecked] &mut numbers[0];
+            #[other::unchecked]
+            let second = #[::you_can::unchecked] &mut numbers[1];
+        }
+    });
+
+    assert_eq!(
+        stripped.to_string(),
+        quote! {
+            fn main() {
+                #[inline]
+                let first = &mut numbers[0];
+                #[other::unchecked]
+                let second = &mut numbers[1];
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn leaves_other_crates_markers() {
+    let (output, report) = you_can_core::suppress(
+        quote! {
+            fn main() {
+                #[other::keep_the_borrow_checker]
+                let first = &mut numbers[0];
+                let second = #[other::unchecked] &mut numbers[1];
+            }
+        },
+        Options::default(),
+    );
+
+    assert_eq!(report.references.len(), 2);
+    let output = output.to_string();
+    assert!(
+        output.contains("# [other :: keep_the_borrow_checker]"),
+        "{output}"
+    );
+    assert!(output.contains("# [other :: unchecked]"), "{output}");
+}
+
+#[test]
+fn derives_borrow_unchecked() {
+    let output = you_can_core::derive_borrow_unchecked(quote! {
+        struct Parser<'src, T: Trait<'src>> {
+            source: &'src [T],
+        }
+    });
+
+    let output = output.to_string();
+    assert!(
+        output.contains("type Unbounded = Parser < '__unbounded , T >"),
+        "{output}"
+    );
+    assert!(output.contains("T : Trait < '__unbounded >"), "{output}");
+
+    let output = you_can_core::derive_borrow_unchecked(quote! {
+        struct Named<'__unbounded>(&'__unbounded mut Vec<u8>);
+    })
+    .to_string();
+    assert!(
+        output.contains("type Unbounded = Named < '__unbounded_ >"),
+        "{output}"
+    );
+
+    let output = you_can_core::derive_borrow_unchecked(quote! {
+        union Either<'a> {
+            shared: &'a u8,
+            number: usize,
+        }
+    });
+    assert!(output.to_string().contains("compile_error"));
+}
+
+#[test]
+fn unbinds_returned_guards() {
+    let (output, report) = you_can_core::suppress(
+        quote! {
+            fn main() {
+                let cell = RefCell::new(1);
+                let guard = cell.borrow_mut();
+                let length = cell.len();
+                let lock = mutex.lock().unwrap();
+            }
+        },
+        Options::default(),
+    );
+
+    let kinds: Vec<ReferenceKind> = report
+        .references
+        .iter()
+        .map(|reference| reference.kind)
+        .collect();
+    assert_eq!(kinds, [ReferenceKind::Call, ReferenceKind::Call]);
+    assert_eq!(output.to_string().matches("Probe").count(), 2, "{output}");
+}
+
+#[test]
+fn unbinds_pinned_references() {
+    let (output, report) = you_can_core::suppress(
+        quote! {
+            fn main() {
+                let pinned = pin!(value);
+                let reborrowed = pinned.as_mut();
+                let mut number = 1;
+                let pinned = Pin::new(&mut number);
+            }
+        },
+        Options::default(),
+    );
+
+    let kinds: Vec<ReferenceKind> = report
+        .references
+        .iter()
+        .map(|reference| reference.kind)
+        .collect();
+    assert_eq!(kinds, [
+        ReferenceKind::Call,
+        ReferenceKind::Call,
+        ReferenceKind::Mut,
+        ReferenceKind::Call
+    ]);
+    // `pin!()` is hoisted into its own `let` statement, so that the value it
+    // pins lives until the end of the block.
+    let output = output.to_string();
+    assert!(
+        output.contains("let pinned_1 = pin ! (value) ;"),
+        "{output}"
+    );
+}
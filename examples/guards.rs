@@ -0,0 +1,15 @@
+use std::sync::{Arc, Mutex};
+
+#[you_can::turn_off_the_borrow_checker]
+pub fn main() {
+    let shared = Arc::new(Mutex::new(vec![1, 2]));
+
+    // moving the `Arc` while its contents are locked, which doesn't move the
+    // `Mutex` itself
+    let mut guard = shared.lock().unwrap();
+    let moved = shared;
+    guard.push(3);
+    drop(guard);
+
+    println!("{:?}", moved.lock().unwrap());
+}
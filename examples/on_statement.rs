@@ -1,4 +1,4 @@
-#![feature(stmt_expr_attributes, proc_macro_hygiene)]
+#![feature(proc_macro_hygiene)]
 
 pub fn main() {
     let mut owned = vec![1, 32];
@@ -0,0 +1,19 @@
+use {std::alloc::System, you_can::quarantine::Quarantine};
+
+#[global_allocator]
+static ALLOCATOR: Quarantine<System> = Quarantine::new(System);
+
+#[you_can::turn_off_the_borrow_checker]
+pub fn main() {
+    let mut owned = vec![1, 32];
+
+    // unsound mutable aliasing
+    let mut_1 = &mut owned[0];
+    let mut_2 = &mut owned[1];
+
+    // use after free, but the memory is never reused
+    drop(owned);
+    let freed = ALLOCATOR.is_freed(mut_1) && ALLOCATOR.is_freed(mut_2);
+    let undefined = *mut_1 + *mut_2;
+    println!("{freed} {undefined}");
+}
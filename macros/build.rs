@@ -1,8 +1,19 @@
 fn main() {
     println!("cargo:rerun-if-env-changed=RUSTC_BOOTSTRAP");
+    println!("cargo:rustc-check-cfg=cfg(rustc_is_unstable)");
     if rustc_is_unstable() {
         println!("cargo:rustc-cfg=rustc_is_unstable");
     }
+
+    println!("cargo:rerun-if-env-changed=YOU_CAN_KEEP_THE_BORROW_CHECKER");
+    println!("cargo:rustc-check-cfg=cfg(you_can_keep_the_borrow_checker)");
+    if std::env::var_os("YOU_CAN_KEEP_THE_BORROW_CHECKER").is_some() {
+        println!("cargo:rustc-cfg=you_can_keep_the_borrow_checker");
+    }
+
+    // Rebuild us, and so re-expand everything using us, when it changes, so
+    // that the reports are written for code that was already built.
+    println!("cargo:rerun-if-env-changed=YOU_CAN_REPORT_DIR");
 }
 
 fn rustc_is_unstable() -> bool {
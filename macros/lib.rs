@@ -3,8 +3,8 @@
 
 use {
     proc_macro::{Span, TokenStream},
-    quote::{quote, quote_spanned, ToTokens},
-    syn::{fold::Fold, parse_quote_spanned, spanned::Spanned, visit::Visit},
+    quote::{quote, quote_spanned},
+    you_can_core::{Options, Report},
 };
 
 /// Runs one of two branches depending on whether we're running on a stable
@@ -30,128 +30,303 @@ macro_rules! if_unstable {
 }
 
 #[proc_macro_attribute]
-pub fn turn_off_the_borrow_checker(_attribute: TokenStream, input: TokenStream) -> TokenStream {
-    let mut suppressor = BorrowCheckerSuppressor {
-        suppressed_references: vec![],
+pub fn turn_off_the_borrow_checker(attribute: TokenStream, input: TokenStream) -> TokenStream {
+    if keeping_the_borrow_checker() {
+        return you_can_core::strip_markers(input.into()).into();
+    }
+
+    let options = match syn::parse(attribute) {
+        Ok(options) => options,
+        Err(error) => {
+            // Leave the input in place, so that we don't cause other errors.
+            let error = error.to_compile_error();
+            let input = proc_macro2::TokenStream::from(input);
+            return quote! { #error #input }.into();
+        },
     };
+    let (output, report) = you_can_core::suppress(input.into(), options);
 
-    let output = if let Ok(as_file) = syn::parse(input.clone()) {
-        suppressor.fold_file(as_file).to_token_stream()
-    } else if let Ok(as_expr) = syn::parse(input.clone()) {
-        suppressor.fold_expr(as_expr).to_token_stream()
-    } else if let Ok(as_stmt) = syn::parse(input) {
-        suppressor.fold_stmt(as_stmt).to_token_stream()
+    let gate = gate();
+    let warnings = emit(report);
+    if let Ok(syn::TraitItemMethod { default: None, .. }) = syn::parse2(output.clone()) {
+        // A required trait method has no body, so there's nothing to suppress.
+        output.into()
+    } else if let Ok(mut function) = syn::parse2::<syn::ImplItemMethod>(output.clone()) {
+        // Functions may be in an `impl` or `trait`, where our unnamed consts
+        // aren't allowed, so we put them at the start of the body instead.
+        let mut stmts = syn::parse2::<Statements>(quote! { #gate #warnings })
+            .unwrap()
+            .0;
+        stmts.append(&mut function.block.stmts);
+        function.block.stmts = stmts;
+        quote! { #function }.into()
+    } else if gate.is_empty() || syn::parse2::<syn::Expr>(output.clone()).is_err() {
+        quote! { #gate #warnings #output }.into()
     } else {
-        return quote! { compile_error!("unsupported use of #[turn_off_the_borrow_checker]") }
-            .into();
+        // We can only put the gate in a statement before an expression.
+        quote! { { #gate #warnings #output } }.into()
+    }
+}
+
+#[proc_macro]
+pub fn borrow_checker_off(input: TokenStream) -> TokenStream {
+    if keeping_the_borrow_checker() {
+        return you_can_core::strip_markers(input.into()).into();
+    }
+
+    let stmts = match syn::parse::Parser::parse(syn::Block::parse_within, input) {
+        Ok(stmts) => stmts,
+        Err(error) => return error.to_compile_error().into(),
     };
 
+    let output = match stmts.as_slice() {
+        // A single expression (including a block) may be in an expression
+        // position, where only an expression is allowed.
+        [syn::Stmt::Expr(expr)] => {
+            let (expr, report) = you_can_core::suppress_expr(expr.clone(), Options::default());
+            let gate = gate();
+            match emit(report) {
+                None if gate.is_empty() => quote! { #expr },
+                warnings => quote! { { #gate #warnings #expr } },
+            }
+        },
+        _ => {
+            let (stmts, report) = you_can_core::suppress_stmts(stmts, Options::default());
+            let gate = gate();
+            let warnings = emit(report);
+            quote! { #gate #warnings #(#stmts)* }
+        },
+    };
+    output.into()
+}
+
+#[proc_macro_derive(BorrowUnchecked)]
+pub fn derive_borrow_unchecked(input: TokenStream) -> TokenStream {
+    you_can_core::derive_borrow_unchecked(input.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn keep_the_borrow_checker(attribute: TokenStream, input: TokenStream) -> TokenStream {
+    marker("keep_the_borrow_checker", attribute, input)
+}
+
+#[proc_macro_attribute]
+pub fn unchecked(attribute: TokenStream, input: TokenStream) -> TokenStream {
+    marker("unchecked", attribute, input)
+}
+
+/// Whether we've been told to leave the borrow checker on everywhere, to check
+/// whether code still needs it turned off. This is usually set when we're
+/// built, so that Cargo rebuilds everything using us when it changes, but we
+/// also check it when we're run, for tools that call `rustc` directly.
+fn keeping_the_borrow_checker() -> bool {
+    cfg!(you_can_keep_the_borrow_checker)
+        || std::env::var_os("YOU_CAN_KEEP_THE_BORROW_CHECKER").is_some()
+}
+
+/// Expands one of our marker attributes. They only have an effect inside of
+/// code that the borrow checker is being turned off for, where they're removed
+/// before they're expanded.
+fn marker(name: &str, attribute: TokenStream, input: TokenStream) -> TokenStream {
+    if !attribute.is_empty() {
+        let error = format!("#[{name}] doesn't take any arguments");
+        let input = proc_macro2::TokenStream::from(input);
+        return quote! {
+            compile_error!(#error);
+            #input
+        }
+        .into();
+    }
+    input
+}
+
+/// The statements inside of a block, without the braces.
+struct Statements(Vec<syn::Stmt>);
+
+impl syn::parse::Parse for Statements {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        syn::Block::parse_within(input).map(Self)
+    }
+}
+
+/// Returns items that refuse to compile if the crate using the macro isn't
+/// allowed to, according to the features that `you-can` was built with.
+fn gate() -> proc_macro2::TokenStream {
+    let mut gate = quote! {};
+    if cfg!(all(
+        feature = "require_acknowledgement",
+        not(feature = "acknowledge_ub")
+    )) {
+        gate.extend(quote! {
+            // The crate using the macro might not declare our cfg.
+            #[allow(unexpected_cfgs)]
+            const _: () = {
+                #[cfg(not(you_can_i_understand_this_is_ub))]
+                compile_error!(
+                "the borrow checker can't be turned off without acknowledging that it produces \
+                undefined behaviour, with `--cfg you_can_i_understand_this_is_ub` or the \
+                    `acknowledge_ub` feature of `you-can`"
+                );
+            };
+        });
+    }
+    if cfg!(feature = "debug_only") {
+        gate.extend(quote! {
+            #[cfg(not(debug_assertions))]
+            compile_error!(
+                "the borrow checker can only be turned off in builds with debug assertions \
+                enabled, because of the `debug_only` feature of `you-can`"
+            );
+        });
+    }
+    gate
+}
+
+/// Reports the references we've suppressed. On nightly, we use warning
+/// diagnostics for this. Otherwise, we return an item that uses deprecated
+/// functions at the location of each reference, so that the deprecation
+/// warnings point at them instead.
+fn emit(report: Report) -> Option<proc_macro2::TokenStream> {
+    if let Some(dir) = std::env::var_os("YOU_CAN_REPORT_DIR") {
+        if let Err(error) = write_report(&report, dir.as_ref()) {
+            eprintln!("warning: couldn't write a you_can report to {dir:?}: {error}");
+        }
+    }
+
     if_unstable! {
         then {
             proc_macro::Diagnostic::spanned(
                 vec![Span::call_site().parent().unwrap_or_else(Span::call_site)],
                 proc_macro::Level::Warning,
-                "this suppresses the borrow checker in an unsafe, unsound, and unstable way \
-                that produces undefined behaviour. this is not suitable for any purpose beyond \
-                educational experimentation.",
+                DANGER,
             ).emit();
 
-            if suppressor.suppressed_references.len() > 1 {
+            if report.references.len() > 1 {
                 proc_macro::Diagnostic::spanned(
-                    suppressor.suppressed_references,
+                    report.references.into_iter().map(|reference| reference.span.unwrap()).collect::<Vec<_>>(),
                     proc_macro::Level::Warning,
                     "the borrow checker is suppressed for these references.",
                 ).emit();
             }
 
-            output.into_token_stream().into()
+            for (method, references) in report.methods {
+                if !references.is_empty() {
+                    proc_macro::Diagnostic::spanned(
+                        references.into_iter().map(|reference| reference.span.unwrap()).collect::<Vec<_>>(),
+                        proc_macro::Level::Warning,
+                        format!("the borrow checker is suppressed for these references in `{method}`."),
+                    ).emit();
+                }
+            }
+
+            None
         } else {
-            static DANGER: std::sync::Once = std::sync::Once::new();
-            DANGER.call_once(|| {
-                eprintln!();
-                eprintln!(" DANGER   This project is using the the #[you_can::turn_off_the_borrow_checker]");
-                eprintln!(" DANGER   macro, which is inherently unsafe, unsound, and unstable. This is not");
-                eprintln!(" DANGER   suitable for any purpose beyond educational experimentation.");
-                eprintln!();
+            let mut groups = vec![(
+                "the borrow checker is suppressed for this reference.".to_string(),
+                report.references,
+            )];
+            for (method, references) in report.methods {
+                groups.push((
+                    format!("the borrow checker is suppressed for this reference in `{method}`."),
+                    references,
+                ));
+            }
+
+            let warnings = groups.into_iter().map(|(note, references)| {
+                let uses = references.iter().map(|reference| {
+                    quote_spanned! { reference.span => borrow_checker_suppressed(); }
+                });
+                quote! {
+                    {
+                        #[deprecated(note = #note)]
+                        const fn borrow_checker_suppressed() {}
+                        #(#uses)*
+                    }
+                }
             });
 
-            quote_spanned! {
-                Span::call_site().into() =>
-                #[warn(unsafe_code)]
-                #output
-            }.into_token_stream().into()
+            let call_site = proc_macro2::Span::call_site();
+            Some(quote_spanned! { call_site =>
+                const _: () = {
+                    #[deprecated(note = #DANGER)]
+                    const fn turn_off_the_borrow_checker() {}
+                    turn_off_the_borrow_checker();
+                    #(#warnings)*
+                };
+            })
         }
     }
 }
 
-/// Replaces all references (&T or &mut T) with unbounded references by wrapping
-/// them in calls to you_can::borrow_unchecked().
-#[derive(Debug, Default)]
-struct BorrowCheckerSuppressor {
-    suppressed_references: Vec<Span>,
-}
-
-impl Fold for BorrowCheckerSuppressor {
-    fn fold_expr(&mut self, node: syn::Expr) -> syn::Expr {
-        match node {
-            syn::Expr::Reference(node) => {
-                let node = syn::fold::fold_expr_reference(self, node);
-                self.suppressed_references.push(node.span().unwrap());
-                syn::Expr::Block(parse_quote_spanned! { node.span() =>
-                    {
-                        let r#ref = #node;
-                        unsafe { ::you_can::borrow_unchecked(r#ref) }
-                    }
-                })
-            },
-            _ => syn::fold::fold_expr(self, node),
-        }
-    }
+/// The warning we give wherever the borrow checker is suppressed.
+const DANGER: &str = "this suppresses the borrow checker in an unsafe, unsound, and unstable way \
+                      that produces undefined behaviour. this is not suitable for any purpose \
+                      beyond educational experimentation.";
 
-    fn fold_expr_if(&mut self, mut node: syn::ExprIf) -> syn::ExprIf {
-        if matches!(*node.cond, syn::Expr::Let(_)) {
-            let mut ref_collector = RefCollector::default();
-            ref_collector.visit_expr(&node.cond);
-            let refs = ref_collector.refs;
-            self.suppressed_references.extend(ref_collector.spans);
-            let then_stmts = node.then_branch.stmts.clone();
-            node.then_branch = parse_quote_spanned! { node.span() =>
-                {
-                    #(let #refs = unsafe { ::you_can::borrow_unchecked(#refs) };)*
-                    #(#then_stmts)*
-                }
-            };
-        }
-        syn::fold::fold_expr_if(self, node)
-    }
+/// Writes a JSON report of the references we've suppressed to a file in
+/// `dir`, named after the crate and the location of the macro call.
+fn write_report(report: &Report, dir: &std::path::Path) -> std::io::Result<()> {
+    let call_site = Span::call_site();
+    let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+    let references = report
+        .references
+        .iter()
+        .chain(report.methods.iter().flat_map(|(_, references)| references));
 
-    fn fold_arm(&mut self, mut node: syn::Arm) -> syn::Arm {
-        let mut ref_collector = RefCollector::default();
-        ref_collector.visit_pat(&node.pat);
-        let refs = ref_collector.refs;
-        self.suppressed_references.extend(ref_collector.spans);
-        let body = node.body.clone();
-        node.body = parse_quote_spanned! { node.span() =>
-            {
-                #(let #refs = unsafe { ::you_can::borrow_unchecked(#refs) };)*
-                #body
-            }
-        };
-        syn::fold::fold_arm(self, node)
+    let mut json = format!(
+        "{{\"crate\":{},\"file\":{},\"line\":{},\"column\":{},\"references\":[",
+        json_string(&crate_name),
+        json_string(&call_site.file()),
+        call_site.line(),
+        call_site.column(),
+    );
+    for (index, reference) in references.enumerate() {
+        let span = reference.span.unwrap();
+        let (start, end) = (span.start(), span.end());
+        json += &format!(
+            "{}{{\"file\":{},\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"\
+             column\":{}}},\"kind\":{},\"item\":{}}}",
+            if index == 0 { "" } else { "," },
+            json_string(&span.file()),
+            start.line(),
+            start.column(),
+            end.line(),
+            end.column(),
+            json_string(reference.kind.as_str()),
+            reference
+                .item
+                .as_deref()
+                .map_or_else(|| "null".to_string(), json_string),
+        );
     }
-}
+    json += "]}\n";
 
-#[derive(Debug, Default)]
-struct RefCollector {
-    refs: Vec<syn::Ident>,
-    spans: Vec<Span>,
+    let name = [
+        crate_name,
+        call_site.file(),
+        call_site.line().to_string(),
+        call_site.column().to_string(),
+    ]
+    .iter()
+    .filter(|part| !part.is_empty())
+    .map(|part| part.replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "_"))
+    .collect::<Vec<_>>()
+    .join("-")
+        + ".json";
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(name), json)
 }
 
-impl<'ast> Visit<'ast> for RefCollector {
-    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
-        if node.by_ref.is_some() {
-            self.refs.push(node.ident.clone());
-            self.spans.push(node.span().unwrap());
+/// Quotes a string for JSON.
+fn json_string(string: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
         }
     }
+    quoted + "\""
 }
@@ -0,0 +1,214 @@
+//! The runtime borrow registry used by
+//! `#[turn_off_the_borrow_checker(checked)]`.
+//!
+//! Instead of leaving the unbounded references it creates unchecked, the macro
+//! registers each one bound by a `let` statement as a loan on the local
+//! variable it borrows from, and reports every other access to the variables it
+//! can see. An access that would conflict with a loan under the borrow
+//! checker's rules (a mutable access, or any access to a mutably borrowed
+//! place) invalidates it, and using an invalidated loan panics, pointing at the
+//! borrow, the conflicting access, and the use.
+//!
+//! This isn't a complete model of Rust's aliasing rules. It only sees the code
+//! that the macro is applied to, and it can't tell how every expression uses
+//! its operands, so it errs on the side of not panicking. For the same reason,
+//! once 256 loans are live at once, any more aren't tracked at all.
+
+use {
+    crate::spin::SpinLock,
+    core::{
+        mem::needs_drop,
+        panic::Location,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// The most loans that can be tracked at once.
+const CAPACITY: usize = 256;
+
+static REGISTRY: SpinLock<[Option<LoanState>; CAPACITY]> = SpinLock::new([None; CAPACITY]);
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy)]
+struct LoanState {
+    id: usize,
+    /// The address of the variable the loan borrows from.
+    root: usize,
+    /// The place the loan borrows, like `x` or `x.field`.
+    place: &'static str,
+    mutable: bool,
+    location: &'static Location<'static>,
+    invalidated_by: Option<Conflict>,
+}
+
+/// An access that invalidated a loan.
+#[derive(Debug, Clone, Copy)]
+struct Conflict {
+    access: Access,
+    place: &'static str,
+    location: &'static Location<'static>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+    Move,
+}
+
+/// A loan on a place, which is released when this is dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct Loan {
+    /// Where the loan is in the registry, or `None` if it was full.
+    index: Option<usize>,
+    id: usize,
+}
+
+impl Loan {
+    /// Panics if a conflicting access has invalidated this loan.
+    #[track_caller]
+    pub fn check(&self) {
+        let Some(index) = self.index else {
+            return;
+        };
+        let loan = REGISTRY.with(|loans| loans[index].filter(|loan| loan.id == self.id));
+        if let Some(LoanState {
+            place,
+            mutable,
+            location,
+            invalidated_by: Some(conflict),
+            ..
+        }) = loan
+        {
+            panic!(
+                "the {kind} borrow of `{place}` at {location} was used at {used}, after it was \
+                 invalidated by {access} of `{conflicting}` at {conflict_location}",
+                kind = if mutable { "mutable" } else { "shared" },
+                used = Location::caller(),
+                access = match conflict.access {
+                    Access::Read => "a shared borrow or read",
+                    Access::Write => "a mutable borrow or write",
+                    Access::Move => "a move",
+                },
+                conflicting = conflict.place,
+                conflict_location = conflict.location,
+            );
+        }
+    }
+}
+
+impl Drop for Loan {
+    fn drop(&mut self) {
+        let Some(index) = self.index else {
+            return;
+        };
+        REGISTRY.with(|loans| {
+            if loans[index].is_some_and(|loan| loan.id == self.id) {
+                loans[index] = None;
+            }
+        });
+    }
+}
+
+/// Registers a loan on a place in the variable that `root` points to, unless
+/// the registry is full, in which case the loan is never checked.
+#[track_caller]
+pub fn loan<Root: ?Sized>(root: *const Root, place: &'static str, mutable: bool) -> Loan {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let state = LoanState {
+        id,
+        root: root as *const () as usize,
+        place,
+        mutable,
+        location: Location::caller(),
+        invalidated_by: None,
+    };
+    let index = REGISTRY.with(|loans| {
+        let index = loans.iter().position(Option::is_none)?;
+        loans[index] = Some(state);
+        Some(index)
+    });
+    Loan { index, id }
+}
+
+/// Reports a shared borrow or read of a place in the variable that `root`
+/// points to, invalidating any mutable loans on it.
+#[track_caller]
+pub fn read<Root: ?Sized>(root: *const Root, place: &'static str) {
+    access(
+        root as *const () as usize,
+        place,
+        Access::Read,
+        Location::caller(),
+    );
+}
+
+/// Reports a mutable borrow or write of a place in the variable that `root`
+/// points to, invalidating any loans on it.
+#[track_caller]
+pub fn write<Root: ?Sized>(root: *const Root, place: &'static str) {
+    access(
+        root as *const () as usize,
+        place,
+        Access::Write,
+        Location::caller(),
+    );
+}
+
+/// Reports a use of the value of a place in the variable that `root` points
+/// to. If its type has drop glue, it can't be `Copy`, so this must be a move,
+/// which invalidates any loans on it. Otherwise, we assume it's a read.
+#[track_caller]
+pub fn value<Root: ?Sized, Value: ?Sized>(
+    root: *const Root,
+    _value: *const Value,
+    place: &'static str,
+) {
+    let access = if needs_drop::<Value>() {
+        Access::Move
+    } else {
+        Access::Read
+    };
+    self::access(
+        root as *const () as usize,
+        place,
+        access,
+        Location::caller(),
+    );
+}
+
+/// Invalidates the loans that conflict with an access.
+fn access(root: usize, place: &'static str, access: Access, location: &'static Location<'static>) {
+    REGISTRY.with(|loans| {
+        for loan in loans.iter_mut().flatten() {
+            if loan.root == root
+                && loan.invalidated_by.is_none()
+                && (access != Access::Read || loan.mutable)
+                && overlaps(loan.place, place)
+            {
+                loan.invalidated_by = Some(Conflict {
+                    access,
+                    place,
+                    location,
+                });
+            }
+        }
+    });
+}
+
+/// Whether two places in the same variable overlap. We only know about
+/// fields, so a place overlaps any place it's a prefix of.
+fn overlaps(a: &str, b: &str) -> bool {
+    let (a, b) = (fields(a), fields(b));
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    longer
+        .strip_prefix(shorter)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// The fields in a place, without the variable they're in.
+fn fields(place: &str) -> &str {
+    place.find('.').map_or("", |index| &place[index..])
+}
@@ -0,0 +1,31 @@
+//! Helpers for the code generated by
+//! [`#[derive(BorrowUnchecked)]`][crate::BorrowUnchecked].
+
+use core::{
+    marker::PhantomData,
+    mem::{align_of, size_of, ManuallyDrop},
+};
+
+/// Fails to compile if `From` and `To` don't have the same size and
+/// alignment, when `CHECK` is used.
+pub struct SameLayout<From, To>(PhantomData<(From, To)>);
+
+impl<From, To> SameLayout<From, To> {
+    pub const CHECK: () = assert!(
+        size_of::<From>() == size_of::<To>() && align_of::<From>() == align_of::<To>(),
+        "BorrowUnchecked::Unbounded must have the same layout as the type it unbinds"
+    );
+}
+
+/// Reinterprets a value as a type that only differs from it in its lifetimes.
+///
+/// # Safety
+///
+/// `To` must be the same type as `From`, except for its lifetimes.
+#[inline(always)]
+pub unsafe fn unbind<From, To>(from: From) -> To {
+    #[allow(clippy::let_unit_value)]
+    let () = SameLayout::<From, To>::CHECK;
+    let from = ManuallyDrop::new(from);
+    unsafe { (&*from as *const From as *const To).read() }
+}
@@ -0,0 +1,154 @@
+//! [`BorrowUnchecked`] for the borrow guards and iterators in the standard
+//! library, and the dispatch that lets
+//! [`#[turn_off_the_borrow_checker]`][crate::turn_off_the_borrow_checker]
+//! unbind the values returned by method calls without knowing their types.
+
+use crate::BorrowUnchecked;
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+/// Implements [`BorrowUnchecked`] for types whose only lifetime parameter is
+/// their first, by replacing it with the unbounded lifetime.
+macro_rules! impl_borrow_unchecked_for_borrows {
+    ($(
+        $(#[$attr:meta])*
+        <$($T:ident $(: ?$Sized:ident)?),*> $($path:ident)::+;
+    )*) => {$(
+        $(#[$attr])*
+        unsafe impl<'original, 'unbounded, $($T: $(?$Sized +)? 'unbounded),*>
+            BorrowUnchecked<'original, 'unbounded> for $($path)::+<'original, $($T),*>
+        {
+            type Unbounded = $($path)::+<'unbounded, $($T),*>;
+
+            #[inline(always)]
+            unsafe fn borrow_unchecked(self) -> Self::Unbounded {
+                unsafe { crate::derive::unbind(self) }
+            }
+        }
+    )*};
+}
+
+impl_borrow_unchecked_for_borrows! {
+    <T: ?Sized> core::cell::Ref;
+    <T: ?Sized> core::cell::RefMut;
+    <T> core::slice::Iter;
+    <T> core::slice::IterMut;
+    <T> core::slice::Chunks;
+    <T> core::slice::ChunksMut;
+    <T> core::slice::Windows;
+    <> core::str::Bytes;
+    <> core::str::CharIndices;
+    <> core::str::Chars;
+    <> core::str::Lines;
+    <> core::str::SplitWhitespace;
+
+    #[cfg(feature = "alloc")]
+    <T> alloc::vec::Drain;
+    #[cfg(feature = "alloc")]
+    <> alloc::string::Drain;
+    #[cfg(feature = "alloc")]
+    <T> alloc::collections::vec_deque::Drain;
+    #[cfg(feature = "alloc")]
+    <T> alloc::collections::vec_deque::Iter;
+    #[cfg(feature = "alloc")]
+    <T> alloc::collections::vec_deque::IterMut;
+    #[cfg(feature = "alloc")]
+    <K, V> alloc::collections::btree_map::Entry;
+    #[cfg(feature = "alloc")]
+    <K, V> alloc::collections::btree_map::Iter;
+    #[cfg(feature = "alloc")]
+    <K, V> alloc::collections::btree_map::IterMut;
+    #[cfg(feature = "alloc")]
+    <K, V> alloc::collections::btree_map::Keys;
+    #[cfg(feature = "alloc")]
+    <K, V> alloc::collections::btree_map::Values;
+    #[cfg(feature = "alloc")]
+    <K, V> alloc::collections::btree_map::ValuesMut;
+    #[cfg(feature = "alloc")]
+    <T> alloc::collections::btree_set::Iter;
+
+    #[cfg(feature = "std")]
+    <T: ?Sized> std::sync::MutexGuard;
+    #[cfg(feature = "std")]
+    <T: ?Sized> std::sync::RwLockReadGuard;
+    #[cfg(feature = "std")]
+    <T: ?Sized> std::sync::RwLockWriteGuard;
+    #[cfg(feature = "std")]
+    <K, V> std::collections::hash_map::Entry;
+    #[cfg(feature = "std")]
+    <K, V> std::collections::hash_map::Iter;
+    #[cfg(feature = "std")]
+    <K, V> std::collections::hash_map::IterMut;
+    #[cfg(feature = "std")]
+    <K, V> std::collections::hash_map::Keys;
+    #[cfg(feature = "std")]
+    <K, V> std::collections::hash_map::Values;
+    #[cfg(feature = "std")]
+    <K, V> std::collections::hash_map::ValuesMut;
+    #[cfg(feature = "std")]
+    <T> std::collections::hash_set::Iter;
+}
+
+/// Borrows a value to find out whether it implements [`BorrowUnchecked`],
+/// using autoref-based specialization: `(&Probe(&value)).tag()` returns an
+/// [`Unbind`] if it does, or a [`Keep`] if it doesn't (or if we can't tell,
+/// like in generic code).
+pub struct Probe<'value, T>(pub &'value T);
+
+/// Returned by [`Probe`] for values that implement [`BorrowUnchecked`].
+pub struct Unbind;
+
+/// Returned by [`Probe`] for values that don't implement
+/// [`BorrowUnchecked`].
+pub struct Keep;
+
+pub trait UnbindTag<'original, 'unbounded> {
+    #[inline(always)]
+    fn tag(&self) -> Unbind {
+        Unbind
+    }
+}
+
+impl<'original, 'unbounded, T: BorrowUnchecked<'original, 'unbounded>>
+    UnbindTag<'original, 'unbounded> for Probe<'_, T>
+{
+}
+
+pub trait KeepTag {
+    #[inline(always)]
+    fn tag(&self) -> Keep {
+        Keep
+    }
+}
+
+impl<T> KeepTag for &Probe<'_, T> {}
+
+impl Unbind {
+    /// Unbinds the lifetimes in a value.
+    ///
+    /// # Safety
+    ///
+    /// _The section intentionally left blank._
+    #[inline(always)]
+    pub unsafe fn unbind<'original, 'unbounded, T: BorrowUnchecked<'original, 'unbounded>>(
+        self,
+        value: T,
+    ) -> T::Unbounded {
+        unsafe { value.borrow_unchecked() }
+    }
+}
+
+impl Keep {
+    /// Returns the value unchanged.
+    ///
+    /// # Safety
+    ///
+    /// None; this is only `unsafe` to match [`Unbind::unbind`].
+    #[inline(always)]
+    pub unsafe fn unbind<T>(self, value: T) -> T {
+        value
+    }
+}
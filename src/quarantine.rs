@@ -0,0 +1,79 @@
+use {
+    crate::spin::SpinLock,
+    core::alloc::{GlobalAlloc, Layout},
+};
+
+/// The byte that freed memory is filled with.
+pub const POISON: u8 = 0xDD;
+
+/// The number of most recently freed blocks that we remember, for
+/// [`Quarantine::is_freed`].
+const CAPACITY: usize = 4096;
+
+/// A [`GlobalAlloc`] wrapper that never reuses freed memory, and fills it with
+/// [`POISON`] instead.
+pub struct Quarantine<Inner> {
+    inner: Inner,
+    freed: SpinLock<Freed>,
+}
+
+/// The most recently freed blocks, as a ring buffer of addresses and sizes.
+struct Freed {
+    blocks: [(usize, usize); CAPACITY],
+    next: usize,
+}
+
+impl<Inner> Quarantine<Inner> {
+    /// Wraps an allocator, like [`std::alloc::System`].
+    ///
+    /// [`std::alloc::System`]: https://doc.rust-lang.org/std/alloc/struct.System.html
+    pub const fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            freed: SpinLock::new(Freed {
+                blocks: [(0, 0); CAPACITY],
+                next: 0,
+            }),
+        }
+    }
+
+    /// Whether a pointer points into one of the most recently freed blocks.
+    ///
+    /// Only the last 4096 blocks to be freed are remembered, so a pointer
+    /// into a block that was freed before those is reported as live, even
+    /// though its memory is still poisoned and never reused.
+    pub fn is_freed<T: ?Sized>(&self, pointer: *const T) -> bool {
+        let address = pointer as *const () as usize;
+        self.freed.with(|freed| {
+            freed
+                .blocks
+                .iter()
+                .any(|&(start, size)| size > 0 && (start..start + size).contains(&address))
+        })
+    }
+}
+
+unsafe impl<Inner: GlobalAlloc> GlobalAlloc for Quarantine<Inner> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        unsafe { self.inner.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        unsafe { self.inner.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // We never give the block back to the inner allocator, so it can't be
+        // reused, and anything that's still pointing into it will only see
+        // the poison.
+        unsafe { ptr.write_bytes(POISON, layout.size()) };
+        // This can't allocate, since that could deadlock.
+        self.freed.with(|freed| {
+            freed.blocks[freed.next] = (ptr as usize, layout.size());
+            freed.next = (freed.next + 1) % CAPACITY;
+        });
+    }
+
+    // The default `realloc` allocates a new block and frees the old one, so
+    // the old one is quarantined too.
+}
@@ -0,0 +1,42 @@
+//! The lock around the global state in `checked` and `quarantine`, which
+//! can't use `std`'s locks or allocate.
+
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A value that's only accessed by one thread at a time, which spins while
+/// waiting for its turn.
+pub(crate) struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// Safety: the value is only accessed while `locked` is held.
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the value. `f` must not panic, or the
+    /// lock will stay locked.
+    pub(crate) fn with<U>(&self, f: impl FnOnce(&mut T) -> U) -> U {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        // Safety: we hold the lock.
+        let output = f(unsafe { &mut *self.value.get() });
+        self.locked.store(false, Ordering::Release);
+        output
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+// Each guard borrows one element of an array while we use the other, which
+// the borrow checker sees as overlapping. Going through an index also keeps
+// the macro from unbinding the receiver, so only the returned guard's own
+// `BorrowUnchecked` impl lets these compile.
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    // vec::Drain
+    let mut vecs = [vec![1, 2, 3], vec![4]];
+    let drain = vecs[0].drain(1..);
+    vecs[1].push(5);
+    assert_eq!(drain.collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(vecs, [vec![1], vec![4, 5]]);
+
+    // string::Drain
+    let mut strings = [String::from("abc"), String::from("d")];
+    let drain = strings[0].drain(1..);
+    strings[1].push('e');
+    assert_eq!(drain.collect::<String>(), "bc");
+    assert_eq!(strings, ["a", "de"]);
+
+    // vec_deque::Drain, Iter, and IterMut
+    let mut deques = [VecDeque::from([1, 2, 3]), VecDeque::from([4])];
+    let iter = deques[0].iter();
+    deques[1].push_back(5);
+    assert_eq!(iter.copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+    let iter = deques[0].iter_mut();
+    deques[1].push_back(6);
+    iter.for_each(|number| *number += 1);
+
+    let drain = deques[0].drain(..1);
+    deques[1].push_back(7);
+    assert_eq!(drain.collect::<Vec<_>>(), [2]);
+    assert_eq!(deques, [VecDeque::from([3, 4]), VecDeque::from([4, 5, 6, 7])]);
+
+    // btree_map::Entry, Iter, IterMut, Keys, Values, and ValuesMut
+    let mut maps = [BTreeMap::from([(1, 10), (2, 20)]), BTreeMap::new()];
+    let entry = maps[0].entry(3);
+    maps[1].insert(1, 1);
+    entry.or_insert(30);
+
+    let iter = maps[0].iter();
+    maps[1].insert(2, 2);
+    assert_eq!(iter.map(|(_, value)| value).sum::<i32>(), 60);
+
+    let iter = maps[0].iter_mut();
+    maps[1].insert(3, 3);
+    iter.for_each(|(_, value)| *value += 1);
+
+    let keys = maps[0].keys();
+    maps[1].insert(4, 4);
+    assert_eq!(keys.sum::<i32>(), 6);
+
+    let values = maps[0].values();
+    maps[1].insert(5, 5);
+    assert_eq!(values.sum::<i32>(), 63);
+
+    let values = maps[0].values_mut();
+    maps[1].insert(6, 6);
+    values.for_each(|value| *value = 0);
+    assert_eq!(maps[0].values().sum::<i32>(), 0);
+    assert_eq!(maps[1].len(), 6);
+
+    // btree_set::Iter
+    let mut sets = [BTreeSet::from([1, 2]), BTreeSet::new()];
+    let iter = sets[0].iter();
+    sets[1].insert(3);
+    assert_eq!(iter.sum::<i32>(), 3);
+    assert_eq!(sets[1].len(), 1);
+}
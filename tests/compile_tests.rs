@@ -13,9 +13,9 @@ fn run_pass() {
         edition: Some("2021".into()),
         target_rustcflags: Some(
             [
-                "--extern you_can",
+                &format!("--extern you_can={}", you_can_rlib()),
                 "--extern unbounded",
-                "-L target/debug/deps",
+                &format!("-L {}", deps_dir().display()),
             ]
             .join(" "),
         ),
@@ -41,9 +41,9 @@ fn compile_fail() {
         edition: Some("2021".into()),
         target_rustcflags: Some(
             [
-                "--extern you_can",
+                &format!("--extern you_can={}", you_can_rlib()),
                 "--extern unbounded",
-                "-L target/debug/deps",
+                &format!("-L {}", deps_dir().display()),
             ]
             .join(" "),
         ),
@@ -56,6 +56,296 @@ fn compile_fail() {
     compiletest::run_tests(&config);
 }
 
+/// Checks that the borrow checker can't be turned off without acknowledging
+/// that it produces undefined behaviour when `you-can` is built with the
+/// `require_acknowledgement` feature, unless we pass
+/// `--cfg you_can_i_understand_this_is_ub`.
+#[test]
+fn require_acknowledgement() {
+    let deps_dir = build_with_features("require_acknowledgement");
+    run_with_features(
+        compiletest::common::Mode::CompileFail,
+        if rustc_is_unstable() {
+            "tests/require_acknowledgement/unstable-fail"
+        } else {
+            "tests/require_acknowledgement/stable-fail"
+        },
+        &deps_dir,
+    );
+    run_with_features(
+        compiletest::common::Mode::RunPass,
+        "tests/require_acknowledgement/pass",
+        &deps_dir,
+    );
+}
+
+/// Checks that the `acknowledge_ub` feature satisfies the
+/// `require_acknowledgement` feature.
+#[test]
+fn acknowledge_ub() {
+    let deps_dir = build_with_features("require_acknowledgement,acknowledge_ub");
+    run_with_features(
+        compiletest::common::Mode::RunPass,
+        "tests/acknowledge_ub/pass",
+        &deps_dir,
+    );
+}
+
+/// Checks that the borrow checker can't be turned off in builds without debug
+/// assertions when `you-can` is built with the `debug_only` feature, but still
+/// can be in builds with them.
+#[test]
+fn debug_only() {
+    let deps_dir = build_with_features("debug_only");
+    run_with_features(
+        compiletest::common::Mode::CompileFail,
+        if rustc_is_unstable() {
+            "tests/debug_only/unstable-fail"
+        } else {
+            "tests/debug_only/stable-fail"
+        },
+        &deps_dir,
+    );
+    run_with_features(
+        compiletest::common::Mode::RunPass,
+        "tests/debug_only/pass",
+        &deps_dir,
+    );
+}
+
+/// Checks that the `Quarantine` allocator from the `quarantine` feature detects
+/// use after free.
+#[test]
+fn quarantine() {
+    let deps_dir = build_with_features("quarantine");
+    run_with_features(
+        compiletest::common::Mode::RunPass,
+        "tests/quarantine/pass",
+        &deps_dir,
+    );
+}
+
+/// Checks that the guards and iterators from `alloc` are unbound with the
+/// `alloc` feature.
+#[test]
+fn alloc_guards() {
+    let deps_dir = build_with_features("alloc");
+    run_with_features(
+        compiletest::common::Mode::RunPass,
+        "tests/alloc/pass",
+        &deps_dir,
+    );
+}
+
+/// Checks that the guards and iterators from `std` are unbound with the `std`
+/// feature.
+#[test]
+fn std_guards() {
+    let deps_dir = build_with_features("std");
+    run_with_features(
+        compiletest::common::Mode::RunPass,
+        "tests/std/pass",
+        &deps_dir,
+    );
+}
+
+/// Builds `you-can` with the given features, in its own target directory so
+/// that it doesn't replace the build the other tests use (or one by another
+/// toolchain), and returns the directory containing it.
+fn build_with_features(features: &str) -> std::path::PathBuf {
+    let target_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("features")
+        .join(if rustc_is_unstable() {
+            "unstable"
+        } else {
+            "stable"
+        })
+        .join(features.replace(',', "-"));
+    let status = std::process::Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--features", features, "--target-dir"])
+        .arg(&target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "failed to build you-can with {features}");
+    target_dir.join("debug").join("deps")
+}
+
+/// Runs the tests in `src_base` against the `you_can` library in `deps_dir`.
+fn run_with_features(mode: compiletest::common::Mode, src_base: &str, deps_dir: &std::path::Path) {
+    let config = compiletest::Config {
+        mode,
+        src_base: std::path::PathBuf::from(src_base),
+        edition: Some("2021".into()),
+        target_rustcflags: Some(format!(
+            "--extern you_can={} -L {}",
+            newest_rlib(deps_dir),
+            deps_dir.display()
+        )),
+        ..Default::default()
+    };
+
+    config.clean_rmeta();
+
+    compiletest::run_tests(&config);
+}
+
+/// Compiles each of the pass tests again with the borrow checker left on, and
+/// compares the list of those that only compile with it turned off to the
+/// list in `depends_on_suppression.txt` next to them, which `BLESS` updates.
+#[test]
+fn pass_with_the_borrow_checker() {
+    let src_base = std::path::PathBuf::from(if rustc_is_unstable() {
+        "tests/unstable-pass"
+    } else {
+        "tests/stable-pass"
+    });
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("borrow_checked");
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let mut tests: Vec<_> = std::fs::read_dir(&src_base)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .collect();
+    tests.sort();
+
+    let mut depends_on_suppression = String::new();
+    for test in tests {
+        let output = std::process::Command::new("rustc")
+            .env("YOU_CAN_KEEP_THE_BORROW_CHECKER", "1")
+            .arg(&test)
+            .args(["--edition", "2021", "--emit", "metadata", "--out-dir"])
+            .arg(&out_dir)
+            .arg("--extern")
+            .arg(format!("you_can={}", you_can_rlib()))
+            .arg("-L")
+            .arg(deps_dir())
+            .output()
+            .unwrap();
+        if !output.status.success() {
+            let name = test.file_name().unwrap().to_string_lossy();
+            depends_on_suppression += &format!("{name}\n");
+        }
+    }
+
+    let expected_path = src_base.join("depends_on_suppression.txt");
+    if std::env::var_os("BLESS").is_some() {
+        std::fs::write(&expected_path, &depends_on_suppression).unwrap();
+    } else {
+        let expected = std::fs::read_to_string(&expected_path).unwrap_or_default();
+        assert_eq!(
+            depends_on_suppression,
+            expected,
+            "the pass tests that depend on suppressing the borrow checker have changed (run with \
+             BLESS=1 to update {})",
+            expected_path.display()
+        );
+    }
+}
+
+/// Checks the report the macro writes when `YOU_CAN_REPORT_DIR` is set, for a
+/// file whose path needs escaping in JSON.
+#[test]
+fn write_report() {
+    let scratch = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("write_report");
+    let _ = std::fs::remove_dir_all(&scratch);
+    let src_dir = scratch.join(r#"a "quoted" \ path"#);
+    std::fs::create_dir_all(&src_dir).unwrap();
+    let src = src_dir.join("main.rs");
+    std::fs::write(
+        &src,
+        "#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut owned = vec![1, 2];
+    let first = &mut owned[0];
+    owned.push(3);
+    *first += 1;
+}
+",
+    )
+    .unwrap();
+
+    let reports = scratch.join("reports");
+    let output = std::process::Command::new("rustc")
+        .env("YOU_CAN_REPORT_DIR", &reports)
+        .env("CARGO_CRATE_NAME", "report")
+        .env_remove("YOU_CAN_KEEP_THE_BORROW_CHECKER")
+        .arg(&src)
+        .args(["--edition", "2021", "--emit", "metadata", "--out-dir"])
+        .arg(&scratch)
+        .arg("--extern")
+        .arg(format!("you_can={}", you_can_rlib()))
+        .arg("-L")
+        .arg(deps_dir())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let written: Vec<_> = std::fs::read_dir(&reports)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(written.len(), 1, "{written:?}");
+    let file = src
+        .display()
+        .to_string()
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#);
+    // Spans can only be joined on nightly, so elsewhere the first reference
+    // ends with its first token.
+    let end = if rustc_is_unstable() { 30 } else { 18 };
+    assert_eq!(
+        std::fs::read_to_string(&written[0]).unwrap(),
+        format!(
+            concat!(
+                r#"{{"crate":"report","file":"{file}","line":1,"column":1,"references":["#,
+                r#"{{"file":"{file}","start":{{"line":4,"column":17}},"#,
+                r#""end":{{"line":4,"column":{end}}},"kind":"&mut","item":"main"}},"#,
+                r#"{{"file":"{file}","start":{{"line":5,"column":5}},"#,
+                r#""end":{{"line":5,"column":10}},"kind":"&mut","item":"main"}}"#,
+                "]}}\n",
+            ),
+            file = file,
+            end = end,
+        )
+    );
+}
+
+/// The directory containing this test binary and the libraries it was built
+/// alongside.
+fn deps_dir() -> std::path::PathBuf {
+    std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_owned()
+}
+
+/// The most recently built `you_can` library. We name it explicitly because
+/// stale builds may be lying around in the same directory.
+fn you_can_rlib() -> String {
+    newest_rlib(&deps_dir())
+}
+
+/// The most recently built `you_can` library in a directory.
+fn newest_rlib(dir: &std::path::Path) -> String {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with("libyou_can-") && name.ends_with(".rlib")
+        })
+        .max_by_key(|path| path.metadata().unwrap().modified().unwrap())
+        .unwrap()
+        .display()
+        .to_string()
+}
+
 fn rustc_is_unstable() -> bool {
     match rustc_version::version_meta().unwrap().channel {
         rustc_version::Channel::Nightly | rustc_version::Channel::Dev => true,
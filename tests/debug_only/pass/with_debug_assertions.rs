@@ -0,0 +1,10 @@
+// compile-flags: -C debug-assertions=on
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut owned = vec![1, 2, 3];
+    let mut_1 = &mut owned[0];
+    let mut_2 = &mut owned[1];
+    *mut_1 += 1;
+    *mut_2 += 1;
+    assert_eq!(owned, [2, 3, 3]);
+}
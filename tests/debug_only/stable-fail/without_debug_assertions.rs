@@ -0,0 +1,11 @@
+// compile-flags: -C debug-assertions=off
+#[you_can::turn_off_the_borrow_checker]
+//~^ ERROR in builds with debug assertions enabled
+//~| WARNING this suppresses the borrow checker
+fn main() {
+    let mut owned = vec![1, 2, 3];
+    let mut_1 = &mut owned[0]; //~ WARNING the borrow checker is suppressed for this reference
+    let mut_2 = &mut owned[1]; //~ WARNING the borrow checker is suppressed for this reference
+    *mut_1 += 1;
+    *mut_2 += 1;
+}
@@ -0,0 +1,51 @@
+// compiletest links to `std` dynamically by default, which bypasses our
+// `#[global_allocator]`.
+// compile-flags: -C prefer-dynamic=no
+use {std::alloc::System, you_can::quarantine::Quarantine};
+
+#[global_allocator]
+static ALLOCATOR: Quarantine<System> = Quarantine::new(System);
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let condition = std::env::args().count() > 0;
+
+    // the temporaries in each of these are extended to the end of `main`
+    let plain = &String::from("plain");
+    let block = { &String::from("block") };
+    let branch = if condition {
+        &String::from("then")
+    } else {
+        &String::from("else")
+    };
+    let other_branch = if !condition {
+        &String::from("then")
+    } else if condition {
+        &mut String::from("else if")
+    } else {
+        &String::from("else")
+    };
+    let arm = match condition {
+        true => &String::from("true"),
+        false => &String::from("false"),
+    };
+    let nested = (1, {
+        let length = plain.len();
+        match length {
+            0 => &String::from("empty"),
+            _ => &String::from("nested"),
+        }
+    });
+
+    for (extended, expected) in [
+        (plain, "plain"),
+        (block, "block"),
+        (branch, "then"),
+        (other_branch, "else if"),
+        (arm, "true"),
+        (nested.1, "nested"),
+    ] {
+        assert!(!ALLOCATOR.is_freed(extended.as_ptr()));
+        assert_eq!(extended, expected);
+    }
+}
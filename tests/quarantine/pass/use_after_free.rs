@@ -0,0 +1,32 @@
+// compiletest links to `std` dynamically by default, which bypasses our
+// `#[global_allocator]`.
+// compile-flags: -C prefer-dynamic=no
+use {
+    std::alloc::System,
+    you_can::quarantine::{Quarantine, POISON},
+};
+
+#[global_allocator]
+static ALLOCATOR: Quarantine<System> = Quarantine::new(System);
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut owned = vec![1_u8, 2];
+    let mut_1 = &mut owned[0];
+    let mut_2 = &mut owned[1];
+    assert!(!ALLOCATOR.is_freed(mut_1));
+
+    // use after free
+    drop(owned);
+    assert!(ALLOCATOR.is_freed(mut_1));
+    assert!(ALLOCATOR.is_freed(mut_2));
+    assert_eq!(*mut_1, POISON);
+    assert_eq!(*mut_2, POISON);
+
+    // only the most recently freed blocks are remembered
+    for _ in 0..4096 {
+        drop(Box::new(0_u8));
+    }
+    assert!(!ALLOCATOR.is_freed(mut_1));
+    assert_eq!(*mut_1, POISON);
+}
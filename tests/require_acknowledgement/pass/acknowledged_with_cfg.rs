@@ -0,0 +1,27 @@
+// compile-flags: --cfg you_can_i_understand_this_is_ub
+struct Stack {
+    items: Vec<i32>,
+}
+
+impl Stack {
+    #[you_can::turn_off_the_borrow_checker]
+    fn push_twice(&mut self) {
+        let last = self.items.last_mut().unwrap();
+        self.items.push(*last);
+        *last += 1;
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut owned = vec![1, 2, 3];
+    let mut_1 = &mut owned[0];
+    let mut_2 = &mut owned[1];
+    *mut_1 += 1;
+    *mut_2 += 1;
+    assert_eq!(owned, [2, 3, 3]);
+
+    let mut stack = Stack { items: vec![1] };
+    stack.push_twice();
+    assert_eq!(stack.items, [2, 1]);
+}
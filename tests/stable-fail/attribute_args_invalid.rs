@@ -0,0 +1,17 @@
+#[you_can::turn_off_the_borrow_checker(shared_only, mut_only)]
+//~^ ERROR `mut_only` can't be used with `shared_only`
+fn conflicting() {}
+
+#[you_can::turn_off_the_borrow_checker(only = "a b")]
+//~^ ERROR expected a variable name, found `a b`
+fn invalid_names() {}
+
+#[you_can::turn_off_the_borrow_checker(everything)]
+//~^ ERROR unknown argument
+fn unknown() {}
+
+fn main() {
+    conflicting();
+    invalid_names();
+    unknown();
+}
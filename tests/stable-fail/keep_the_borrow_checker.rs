@@ -0,0 +1,43 @@
+#[you_can::turn_off_the_borrow_checker] //~ WARNING this suppresses the borrow checker
+mod lesson {
+    pub fn block() {
+        let mut numbers = vec![1, 2];
+
+        #[you_can::keep_the_borrow_checker]
+        {
+            let first = &mut numbers[0];
+            numbers.push(3);
+            //~^ ERROR cannot borrow `numbers` as mutable more than once at a time
+            *first = 4;
+        }
+    }
+
+    pub fn closure() {
+        let mut numbers = vec![1, 2];
+
+        let mut push = #[you_can::keep_the_borrow_checker]
+        || {
+            let first = &mut numbers[0];
+            numbers.push(3);
+            //~^ ERROR cannot borrow `numbers` as mutable more than once at a time
+            *first = 4;
+        };
+        push();
+    }
+
+    #[you_can::keep_the_borrow_checker]
+    pub fn function() {
+        let mut numbers = vec![1, 2];
+
+        let first = &mut numbers[0];
+        numbers.push(3);
+        //~^ ERROR cannot borrow `numbers` as mutable more than once at a time
+        *first = 4;
+    }
+}
+
+fn main() {
+    lesson::block();
+    lesson::closure();
+    lesson::function();
+}
@@ -0,0 +1,62 @@
+// run-pass
+#[you_can::turn_off_the_borrow_checker(shared_only)]
+fn shared_only() {
+    let mut numbers = vec![1, 2];
+
+    // mutating a value while it's immutably borrowed
+    let first = &numbers[0];
+    numbers[0] = 3;
+    assert_eq!(*first, 3);
+}
+
+#[you_can::turn_off_the_borrow_checker(mut_only)]
+fn mut_only() {
+    let mut numbers = vec![1, 2];
+
+    // reading a value while it's mutably borrowed
+    let first = &mut numbers[0];
+    assert_eq!(numbers.len(), 2);
+    *first = 3;
+    assert_eq!(numbers, [3, 2]);
+}
+
+#[you_can::turn_off_the_borrow_checker(only = "suppressed")]
+fn only() {
+    let mut suppressed = vec![1, 2];
+    let mut checked = vec![3, 4];
+
+    // aliasing one value, but not another
+    let first = &mut suppressed[0];
+    suppressed.push(3);
+    *first = 5;
+    let last = checked.last_mut().unwrap();
+    *last = 6;
+    checked.push(7);
+
+    assert_eq!(suppressed, [5, 2, 3]);
+    assert_eq!(checked, [3, 6, 7]);
+}
+
+#[you_can::turn_off_the_borrow_checker(except = "checked")]
+fn except() {
+    let mut suppressed = vec![1, 2];
+    let mut checked = vec![3, 4];
+
+    // aliasing one value, but not another
+    let first = &mut suppressed[0];
+    suppressed.push(3);
+    *first = 5;
+    let last = checked.last_mut().unwrap();
+    *last = 6;
+    checked.push(7);
+
+    assert_eq!(suppressed, [5, 2, 3]);
+    assert_eq!(checked, [3, 6, 7]);
+}
+
+fn main() {
+    shared_only();
+    mut_only();
+    only();
+    except();
+}
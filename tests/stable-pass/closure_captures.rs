@@ -0,0 +1,64 @@
+// run-pass
+struct Builder {
+    parts: Vec<&'static str>,
+}
+
+impl Builder {
+    fn finish(self) -> String {
+        self.parts.concat()
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut numbers = vec![1];
+
+    // using a variable while a closure has it mutably borrowed
+    let mut push = |number| numbers.push(number);
+    numbers.push(2);
+    push(3);
+    push(4);
+
+    // a closure borrowing a variable from a thread that may outlive it
+    let mut results = vec![];
+    let thread = std::thread::spawn(|| results.push(5));
+    thread.join().unwrap();
+
+    // assigning to a variable while a closure has it mutably borrowed
+    let mut count = 0;
+    let mut increment = || count += 1;
+    count += 10;
+    increment();
+
+    extend(&mut numbers);
+
+    // a closure moving a variable into a method taking `self` by value
+    let mut builder = Builder { parts: vec![] };
+    builder.parts.push("done");
+    let finish = || builder.finish();
+    assert_eq!(finish(), "done");
+
+    assert_eq!(numbers, [1, 2, 3, 4, 6, 7]);
+    assert_eq!(results, [5]);
+    assert_eq!(count, 11);
+
+    // closures borrowing a variable that isn't `mut` from a thread
+    let shared = vec![1, 2];
+    let thread = std::thread::spawn(|| println!("{:?}", shared.len()));
+    thread.join().unwrap();
+    let thread = std::thread::spawn(|| shared.len() as i32 + shared[0]);
+    assert_eq!(thread.join().unwrap(), 3);
+
+    // and one moving it, which we leave alone
+    let total = || shared.into_iter().sum::<i32>();
+    assert_eq!(total(), 3);
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn extend(numbers: &mut Vec<i32>) {
+    // using a reference while a closure has reborrowed it
+    let mut push = |number| numbers.push(number);
+    let first = numbers.len() as i32;
+    push(first + 2);
+    push(first + 3);
+}
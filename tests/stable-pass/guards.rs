@@ -0,0 +1,41 @@
+// run-pass
+use std::{cell::RefCell, io::Read};
+
+struct Numbers(Vec<u32>);
+
+impl Numbers {
+    // an iterator that doesn't implement `BorrowUnchecked`, which is left alone
+    fn iter(&self) -> std::vec::IntoIter<u32> {
+        self.0.clone().into_iter()
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    // holding a borrow guard while borrowing again
+    let cell = RefCell::new(vec![1, 2]);
+    let mut guard = cell.borrow_mut();
+    let shared = &cell;
+    guard.push(3);
+    assert_eq!(shared.as_ptr() as *const _, &*guard as *const _);
+
+    // mutating a vector while iterating over it
+    let mut numbers = vec![1, 2, 3];
+    let mut iter = numbers.iter_mut();
+    numbers[0] = 10;
+    assert_eq!(iter.next(), Some(&mut 10));
+
+    // a string while iterating over its characters
+    let mut text = String::from("ab");
+    let mut chars = text.chars();
+    text.make_ascii_uppercase();
+    assert_eq!(chars.next(), Some('A'));
+
+    // values that don't borrow anything are left alone
+    let mut buffer = [0u8; 2];
+    let read = (&[1u8, 2][..]).read(&mut buffer).unwrap();
+    assert_eq!(read, 2);
+    let numbers = Numbers(vec![4, 5]);
+    let iter = numbers.iter();
+    assert_eq!(iter.sum::<u32>(), 9);
+}
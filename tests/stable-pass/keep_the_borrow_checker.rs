@@ -0,0 +1,92 @@
+// run-pass
+#[you_can::turn_off_the_borrow_checker]
+mod lesson {
+    pub fn suppressed() -> Vec<i32> {
+        let mut numbers = vec![1, 2];
+
+        // unsound mutable aliasing
+        let first = &mut numbers[0];
+        numbers.push(3);
+        *first = 4;
+
+        // kept blocks, statements, and closures
+        #[you_can::keep_the_borrow_checker]
+        {
+            let last = numbers.last_mut().unwrap();
+            *last = 5;
+        }
+        #[you_can::keep_the_borrow_checker]
+        let second = &mut numbers[1];
+        *second = 6;
+        #[you_can::keep_the_borrow_checker]
+        numbers.push(7);
+        #[you_can::keep_the_borrow_checker]
+        let Some(last) = numbers.last() else { unreachable!() };
+        assert_eq!(*last, 7);
+        let double = #[you_can::keep_the_borrow_checker]
+        |numbers: &mut Vec<i32>| {
+            for number in numbers.iter_mut() {
+                *number *= 2;
+            }
+        };
+        double(&mut numbers);
+
+        // a kept nested function
+        #[you_can::keep_the_borrow_checker]
+        fn push(numbers: &mut Vec<i32>) {
+            let len = numbers.len() as i32;
+            numbers.push(len);
+        }
+        push(&mut numbers);
+
+        numbers
+    }
+
+    #[you_can::keep_the_borrow_checker]
+    pub fn checked(numbers: &mut Vec<i32>) {
+        let first = &mut numbers[0];
+        *first = 0;
+        numbers.push(1);
+    }
+
+    pub struct Counter(pub i32);
+
+    #[you_can::keep_the_borrow_checker]
+    impl Counter {
+        pub fn bump(&mut self) -> &mut i32 {
+            let count = &mut self.0;
+            *count += 1;
+            count
+        }
+    }
+
+    impl Counter {
+        #[you_can::keep_the_borrow_checker]
+        pub fn bump_twice(&mut self) {
+            self.bump();
+            self.bump();
+        }
+    }
+
+    #[you_can::keep_the_borrow_checker]
+    pub mod nested {
+        pub fn checked(numbers: &[i32]) -> i32 {
+            let first = &numbers[0];
+            *first
+        }
+    }
+}
+
+fn main() {
+    let mut numbers = lesson::suppressed();
+    assert_eq!(numbers, [8, 12, 10, 14, 4]);
+
+    lesson::checked(&mut numbers);
+    assert_eq!(numbers, [0, 12, 10, 14, 4, 1]);
+
+    let mut counter = lesson::Counter(0);
+    counter.bump_twice();
+    assert_eq!(*counter.bump(), 3);
+
+    assert_eq!(lesson::nested::checked(&numbers), 0);
+}
@@ -0,0 +1,27 @@
+#[you_can::turn_off_the_borrow_checker]
+fn take_inner(source: &mut Option<Vec<i32>>) -> &'static mut Vec<i32> {
+    // the pattern binds a reference to the inside of `source`
+    let inner = match source {
+        Some(inner) => inner,
+        None => panic!(),
+    };
+
+    // use after free
+    *source = None;
+    inner
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut source = Some(vec![1]);
+
+    if let Some(inner) = &source {
+        // use after free
+        source = None;
+        println!("{inner:?}");
+    }
+
+    let mut source = Some(vec![2]);
+    let inner = take_inner(&mut source);
+    inner.push(3);
+}
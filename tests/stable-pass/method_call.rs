@@ -0,0 +1,13 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut owned = vec![1, 32];
+
+    // unsound mutable aliasing through an implicit autoref
+    let first = owned.first_mut().unwrap();
+    owned.push(64);
+
+    // use after free
+    drop(owned);
+    *first += 1;
+    println!("{first}");
+}
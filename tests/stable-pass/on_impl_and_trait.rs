@@ -0,0 +1,57 @@
+// run-pass
+#[derive(Debug, Default)]
+struct Counter {
+    counts: Vec<u32>,
+}
+
+#[you_can::turn_off_the_borrow_checker]
+impl Counter {
+    const EMPTY: &'static [u32] = &[];
+
+    // mutating self while holding a reference into it
+    fn bump_first(&mut self) -> &mut u32 {
+        let first = self.counts.first_mut().unwrap();
+        self.counts.push(0);
+        *first += 1;
+        first
+    }
+
+    fn total(&self) -> u32 {
+        let counts = &self.counts;
+        counts.iter().sum()
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+trait Bump: Sized {
+    const STEP: &'static u32 = &1;
+
+    fn counts(&mut self) -> &mut Vec<u32>;
+
+    // mutating self while iterating over it, in a default method body
+    fn bump_all(&mut self) {
+        let counts = self.counts();
+        for count in counts.iter_mut() {
+            self.counts().reserve(1);
+            *count += *Self::STEP;
+        }
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+impl Bump for Counter {
+    fn counts(&mut self) -> &mut Vec<u32> {
+        let counts = &mut self.counts;
+        self.counts.reserve(1);
+        counts
+    }
+}
+
+fn main() {
+    let mut counter = Counter { counts: vec![0] };
+    *counter.bump_first() += 1;
+    counter.bump_all();
+    assert_eq!(counter.counts, [3, 1]);
+    assert_eq!(counter.total(), 4);
+    assert_eq!(Counter::EMPTY, []);
+}
@@ -0,0 +1,60 @@
+// run-pass
+use std::pin::{pin, Pin};
+
+use you_can::borrow_unchecked;
+
+#[derive(Debug, PartialEq)]
+struct Unmovable {
+    value: u32,
+    _pinned: std::marker::PhantomPinned,
+}
+
+impl Unmovable {
+    fn new(value: u32) -> Self {
+        Unmovable {
+            value,
+            _pinned: std::marker::PhantomPinned,
+        }
+    }
+
+    fn bump(self: Pin<&mut Self>) {
+        unsafe { self.get_unchecked_mut() }.value += 1;
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn with_macro() {
+    // holding a pinned reference from `pin!()` beyond the end of its block
+    let escaped;
+    {
+        let mut pinned = pin!(Unmovable::new(1));
+        pinned.as_mut().bump();
+        escaped = pinned;
+    }
+    assert_eq!(escaped.value, 2);
+
+    // reborrowing a pinned reference twice at once
+    let mut pinned = pin!(Unmovable::new(1));
+    let first = pinned.as_mut();
+    let second = pinned.as_mut();
+    first.bump();
+    second.bump();
+    assert_eq!(pinned.value, 3);
+
+    // using a value while it's pinned through `Pin::new()`
+    let mut number = 1;
+    let pinned = Pin::new(&mut number);
+    number += 1;
+    assert_eq!(*pinned, 2);
+    assert_eq!(number, 2);
+}
+
+fn main() {
+    let mut number = 1;
+    let pinned = unsafe { borrow_unchecked(Pin::new(&mut number)) };
+    number += 1;
+    assert_eq!(*pinned, 2);
+    assert_eq!(number, 2);
+
+    with_macro();
+}
@@ -0,0 +1,14 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut pair = (vec![1], vec![2]);
+
+    // unsound mutable aliasing
+    let (ref mut left_1, _) = pair;
+    let (ref mut left_2, _) = pair;
+    left_1.push(3);
+    left_2.push(4);
+
+    // use after free
+    drop(pair);
+    println!("{left_1:?}");
+}
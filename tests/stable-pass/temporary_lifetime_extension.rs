@@ -0,0 +1,22 @@
+// run-pass
+
+struct Named<'a> {
+    name: &'a String,
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    // these temporaries live until the end of the block, as they would without
+    // the macro
+    let name = &String::from("name");
+    let tuple = (1, &String::from("tuple"));
+    let array = [&String::from("array")];
+    let named = Named {
+        name: &String::from("named"),
+    };
+
+    assert_eq!(name, "name");
+    assert_eq!(tuple.1, "tuple");
+    assert_eq!(array[0], "array");
+    assert_eq!(named.name, "named");
+}
@@ -0,0 +1,69 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, RwLock},
+};
+
+// Each guard borrows one element of an array while we use the other, which
+// the borrow checker sees as overlapping. Going through an index also keeps
+// the macro from unbinding the receiver, so only the returned guard's own
+// `BorrowUnchecked` impl lets these compile.
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    // MutexGuard
+    let mut mutexes = [Mutex::new(1), Mutex::new(2)];
+    let mut guard = mutexes[0].lock().unwrap();
+    *mutexes[1].get_mut().unwrap() += 1;
+    *guard += 1;
+    drop(guard);
+    assert_eq!(*mutexes[0].get_mut().unwrap(), 2);
+    assert_eq!(*mutexes[1].get_mut().unwrap(), 3);
+
+    // RwLockReadGuard and RwLockWriteGuard
+    let mut locks = [RwLock::new(1), RwLock::new(2)];
+    let read = locks[0].read().unwrap();
+    *locks[1].get_mut().unwrap() += 1;
+    assert_eq!(*read, 1);
+    drop(read);
+
+    let mut write = locks[0].write().unwrap();
+    *locks[1].get_mut().unwrap() += 1;
+    *write += 1;
+    drop(write);
+    assert_eq!(*locks[0].get_mut().unwrap(), 2);
+    assert_eq!(*locks[1].get_mut().unwrap(), 4);
+
+    // hash_map::Entry, Iter, IterMut, Keys, Values, and ValuesMut
+    let mut maps = [HashMap::from([(1, 10), (2, 20)]), HashMap::new()];
+    let entry = maps[0].entry(3);
+    maps[1].insert(1, 1);
+    entry.or_insert(30);
+
+    let iter = maps[0].iter();
+    maps[1].insert(2, 2);
+    assert_eq!(iter.map(|(_, value)| value).sum::<i32>(), 60);
+
+    let iter = maps[0].iter_mut();
+    maps[1].insert(3, 3);
+    iter.for_each(|(_, value)| *value += 1);
+
+    let keys = maps[0].keys();
+    maps[1].insert(4, 4);
+    assert_eq!(keys.sum::<i32>(), 6);
+
+    let values = maps[0].values();
+    maps[1].insert(5, 5);
+    assert_eq!(values.sum::<i32>(), 63);
+
+    let values = maps[0].values_mut();
+    maps[1].insert(6, 6);
+    values.for_each(|value| *value = 0);
+    assert_eq!(maps[0].values().sum::<i32>(), 0);
+    assert_eq!(maps[1].len(), 6);
+
+    // hash_set::Iter
+    let mut sets = [HashSet::from([1, 2]), HashSet::new()];
+    let iter = sets[0].iter();
+    sets[1].insert(3);
+    assert_eq!(iter.sum::<i32>(), 3);
+    assert_eq!(sets[1].len(), 1);
+}
@@ -0,0 +1,28 @@
+#[you_can::turn_off_the_borrow_checker(shared_only)] //~ WARNING this suppresses the borrow checker
+fn shared_only() {
+    let mut numbers = vec![1, 2];
+
+    let first = &mut numbers[0];
+    numbers.push(3);
+    //~^ ERROR cannot borrow `numbers` as mutable more than once at a time
+    *first = 4;
+}
+
+#[you_can::turn_off_the_borrow_checker(only = "suppressed")] //~ WARNING this suppresses the borrow checker
+fn only() {
+    let mut suppressed = vec![1, 2];
+    let mut checked = vec![3, 4];
+
+    let first = &mut suppressed[0]; //~ WARNING the borrow checker is suppressed for these references
+    suppressed.push(3);
+    *first = 5;
+    let first = &mut checked[0];
+    checked.push(3);
+    //~^ ERROR cannot borrow `checked` as mutable more than once at a time
+    *first = 5;
+}
+
+fn main() {
+    shared_only();
+    only();
+}
@@ -0,0 +1,10 @@
+use you_can::BorrowUnchecked;
+
+#[derive(BorrowUnchecked)]
+union Either<'a> {
+    //~^ ERROR #[derive(BorrowUnchecked)] doesn't support unions
+    shared: &'a u8,
+    number: usize,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#[you_can::turn_off_the_borrow_checker(marked_only)] //~ WARNING this suppresses the borrow checker
+fn main() {
+    let mut numbers = vec![1, 2];
+
+    let first = #[you_can::unchecked] &mut numbers[0]; //~ WARNING the borrow checker is suppressed for these references
+    numbers.push(3);
+    *first = 4;
+
+    let first = &mut numbers[0];
+    numbers.push(3);
+    //~^ ERROR cannot borrow `numbers` as mutable more than once at a time
+    *first = 4;
+
+    #[you_can::unchecked]
+    let _ = &numbers[0];
+    let first = &mut numbers[0];
+    numbers.push(3);
+    //~^ ERROR cannot borrow `numbers` as mutable more than once at a time
+    *first = 4;
+}
@@ -0,0 +1,123 @@
+// run-pass
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, JoinHandle, Thread},
+};
+
+#[you_can::turn_off_the_borrow_checker]
+async fn push_twice(numbers: &mut Vec<i32>) {
+    // holding a reference across an await while it's aliased
+    let first = &mut numbers[0];
+    numbers.push(2);
+    yield_now().await;
+    *first = 10;
+    numbers.push(3);
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut numbers = vec![1];
+    block_on(push_twice(&mut numbers));
+
+    // a task borrowing a local variable
+    let task = spawn(async {
+        yield_now().await;
+        numbers.push(4);
+    });
+    task.join().unwrap();
+
+    // a task moving a reference to a local variable
+    let last = numbers.last_mut().unwrap();
+    let task = spawn(async move {
+        yield_now().await;
+        *last += 1;
+    });
+    task.join().unwrap();
+
+    // using a variable while an async block has it borrowed
+    let len = async { numbers.len() };
+    numbers.push(6);
+    assert_eq!(block_on(len), 5);
+
+    push_later(&mut numbers).join().unwrap();
+
+    assert_eq!(numbers, [10, 2, 3, 5, 6, 7]);
+
+    // a task borrowing a variable that isn't `mut`
+    let label = String::from("numbers");
+    let task = spawn(async {
+        yield_now().await;
+        label.len()
+    });
+    assert_eq!(task.join().unwrap(), 7);
+
+    // a task moving a reference held by a variable that isn't `mut`, and a
+    // value that isn't a reference
+    let text = label.as_str();
+    let offset = 1;
+    let task = spawn(async move {
+        yield_now().await;
+        text.len() + offset
+    });
+    assert_eq!(task.join().unwrap(), 8);
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn push_later(numbers: &mut Vec<i32>) -> JoinHandle<()> {
+    // a task moving a reference that doesn't live long enough, and a local
+    // variable, into itself
+    let mut number = 6;
+    spawn(async move {
+        yield_now().await;
+        number += 1;
+        numbers.push(number);
+    })
+}
+
+/// Runs a future to completion on the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Runs a future to completion on a new thread.
+fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send,
+{
+    thread::spawn(move || block_on(future))
+}
+
+/// Yields once to the executor.
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|context| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
@@ -0,0 +1,20 @@
+mod inner {
+    you_can::borrow_checker_off! {
+        pub fn main() {
+            let mut owned = vec![1, 32];
+
+            // unsound mutable aliasing
+            let mut_1 = &mut owned[0];
+            let mut_2 = &mut owned[1];
+
+            // use after free
+            drop(owned);
+            let undefined = *mut_1 + *mut_2;
+            println!("{undefined}");
+        }
+    }
+}
+
+fn main() {
+    inner::main();
+}
@@ -0,0 +1,75 @@
+// run-pass
+use you_can::borrow_unchecked;
+
+fn option() {
+    let mut number = 1;
+    let some = unsafe { borrow_unchecked(Some(&mut number)) };
+    number += 1;
+    *some.unwrap() += 1;
+    assert_eq!(number, 3);
+
+    let none = unsafe { borrow_unchecked(None::<&u8>) };
+    assert_eq!(none, None);
+}
+
+fn result() {
+    let mut number = 1;
+    let ok = unsafe { borrow_unchecked(Ok::<_, String>(&mut number)) };
+    number += 1;
+    *ok.unwrap() += 1;
+    assert_eq!(number, 3);
+
+    // the error isn't unbound, so it can be anything
+    let err = unsafe { borrow_unchecked(Err::<&u8, _>(String::from("error"))) };
+    assert_eq!(err, Err(String::from("error")));
+}
+
+fn tuples() {
+    let mut first = 1;
+    let mut second = String::from("two");
+    let (first_ref, second_ref) = unsafe { borrow_unchecked((&mut first, &second)) };
+    first += 1;
+    second.push('!');
+    *first_ref += 1;
+    assert_eq!(first, 3);
+    assert_eq!(second_ref, "two!");
+
+    let (single,) = unsafe { borrow_unchecked((&first,)) };
+    first += 1;
+    assert_eq!(*single, 4);
+    assert_eq!(first, 4);
+}
+
+fn arrays() {
+    let mut numbers = [1, 2];
+    let [first, second] = unsafe {
+        let (first, rest) = numbers.split_first_mut().unwrap();
+        borrow_unchecked([first, &mut rest[0]])
+    };
+    numbers[0] += 10;
+    *first += 1;
+    *second += 1;
+    assert_eq!(numbers, [12, 3]);
+
+    let empty = unsafe { borrow_unchecked::<[&mut u8; 0]>([]) };
+    assert!(empty.is_empty());
+}
+
+fn nested() {
+    let mut number = 1;
+    let text = String::from("text");
+    let pair = unsafe { borrow_unchecked(Some((&mut number, &text))) };
+    number += 1;
+    drop(text);
+    let (number_ref, _text_ref) = pair.unwrap();
+    *number_ref += 1;
+    assert_eq!(number, 3);
+}
+
+fn main() {
+    option();
+    result();
+    tuples();
+    arrays();
+    nested();
+}
@@ -0,0 +1,81 @@
+// run-pass
+struct Builder {
+    parts: Vec<&'static str>,
+}
+
+impl Builder {
+    fn add(&mut self, part: &'static str) {
+        self.parts.push(part);
+    }
+
+    fn finish(self) -> String {
+        self.parts.concat()
+    }
+}
+
+/// A builder whose methods are named like mutating ones, but take `self` by
+/// value.
+#[derive(Default)]
+struct Request {
+    headers: Vec<&'static str>,
+    name: &'static str,
+    body: String,
+}
+
+impl Request {
+    fn push(mut self, header: &'static str) -> Self {
+        self.headers.push(header);
+        self
+    }
+
+    fn insert(mut self, index: usize, header: &'static str) -> Self {
+        self.headers.insert(index, header);
+        self
+    }
+
+    fn set_name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    fn body_mut(mut self, body: &str) -> Self {
+        self.body.push_str(body);
+        self
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    // methods taking `self` by value on `mut` bindings
+    let mut b = Builder { parts: vec![] };
+    b.add("a");
+    b.add("b");
+    assert_eq!(b.finish(), "ab");
+
+    let mut request = Request::default();
+    request = request.push("a");
+    request = request.insert(0, "b");
+    request = request.set_name("c");
+    request = request.body_mut("d");
+    assert_eq!(request.headers, ["b", "a"]);
+    assert_eq!((request.name, request.body.as_str()), ("c", "d"));
+
+    let v = vec![1, 2, 3];
+    let mut it = v.iter();
+    it.next();
+    assert_eq!(it.take(2).count(), 2);
+    let mut it = v.iter();
+    it.next();
+    assert_eq!(it.last(), Some(&3));
+    let mut it = v.iter();
+    it.next();
+    assert_eq!(it.count(), 2);
+
+    // and mutable aliasing through methods taking `self` by reference
+    let mut owned = Vec::with_capacity(3);
+    owned.extend([1, 2]);
+    let first = owned.first_mut().unwrap();
+    owned.push(3);
+    *first += 1;
+    assert_eq!(owned, [2, 2, 3]);
+}
@@ -0,0 +1,77 @@
+// run-pass
+#[you_can::turn_off_the_borrow_checker(checked)]
+fn valid() -> Vec<i32> {
+    let mut numbers = vec![1, 2];
+    let first = &mut numbers[0];
+    *first += 10;
+    numbers.push(3);
+    let last = &numbers[2];
+    let total = numbers[0] + *last;
+    numbers.push(total);
+    numbers
+}
+
+#[you_can::turn_off_the_borrow_checker(checked)]
+fn aliased() {
+    let mut numbers = vec![1, 2];
+    let first = &mut numbers[0];
+    numbers.push(3);
+    *first = 4;
+}
+
+#[you_can::turn_off_the_borrow_checker(checked)]
+fn moved() -> usize {
+    let names = vec![String::from("a")];
+    let name = &names[0];
+    let moved = names;
+    drop(moved);
+    name.len()
+}
+
+#[you_can::turn_off_the_borrow_checker(checked)]
+fn field() -> i32 {
+    let mut pair = (1, 2);
+    let left = &mut pair.0;
+    pair.1 += 1;
+    *left += pair.1;
+    pair.0
+}
+
+#[you_can::turn_off_the_borrow_checker(checked)]
+fn nested(depth: usize) -> usize {
+    let mut numbers = vec![depth];
+    let first = &mut numbers[0];
+    if depth > 0 {
+        *first += nested(depth - 1);
+    }
+    *first
+}
+
+fn main() {
+    assert_eq!(valid(), [11, 2, 3, 14]);
+    assert_eq!(field(), 4);
+    // More loans than the registry can track are live at once.
+    assert_eq!(nested(300), 300 * 301 / 2);
+
+    std::panic::set_hook(Box::new(|_| {}));
+    let message = |f: fn()| {
+        *std::panic::catch_unwind(f)
+            .unwrap_err()
+            .downcast::<String>()
+            .unwrap()
+    };
+
+    let aliased = message(aliased);
+    assert!(aliased.starts_with("the mutable borrow of `numbers` at "));
+    assert!(aliased.contains(".rs:17:17 was used at "));
+    assert!(aliased.contains(".rs:19:5, after it was invalidated by a mutable borrow or write"));
+    assert!(aliased.ends_with(".rs:18:5"));
+
+    let moved = message(|| {
+        moved();
+    });
+    assert!(moved.starts_with("the shared borrow of `names` at "));
+    assert!(moved.contains(".rs:25:16 was used at "));
+    assert!(moved.contains(".rs:28:5, after it was invalidated by a move of `names`"));
+    assert!(moved.ends_with(".rs:26:17"));
+}
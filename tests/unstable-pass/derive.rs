@@ -0,0 +1,64 @@
+// run-pass
+use you_can::{borrow_unchecked, BorrowUnchecked};
+
+#[derive(BorrowUnchecked)]
+struct Parser<'src> {
+    source: &'src str,
+    position: usize,
+}
+
+#[derive(BorrowUnchecked)]
+enum Token<'src, 'buf, T: Copy + 'src, const N: usize>
+where
+    T: PartialEq<T>,
+{
+    Word(&'src str),
+    Buffered(&'buf mut [T; N]),
+}
+
+// a lifetime with the same name as the ones in the generated code
+#[derive(BorrowUnchecked)]
+struct Named<'__unbounded>(&'__unbounded mut Vec<u8>);
+
+fn tokens() {
+    let mut source = String::from("a b");
+    let mut buffer = [1, 2];
+
+    let word = Token::<u8, 2>::Word(&source[..1]);
+    let buffered = Token::<u8, 2>::Buffered(&mut buffer);
+    let (word, buffered) = unsafe { borrow_unchecked((word, buffered)) };
+
+    source.make_ascii_uppercase();
+    buffer[0] = 3;
+
+    match (word, buffered) {
+        (Token::Word(word), Token::Buffered(buffered)) => {
+            assert_eq!(word, "A");
+            assert_eq!(buffered, &[3, 2]);
+        },
+        _ => unreachable!(),
+    }
+    assert_eq!(buffer, [3, 2]);
+}
+
+fn main() {
+    let mut source = String::from("hello, world");
+
+    let parser = unsafe { borrow_unchecked(Parser { source: &source, position: 0 }) };
+    let mut other = Parser { source: &source, position: 0 };
+    let other = unsafe { other.borrow_unchecked_mut() };
+
+    source.make_ascii_uppercase();
+    other.position = 7;
+
+    assert_eq!(parser.source, "HELLO, WORLD");
+    assert_eq!(&other.source[other.position..], "WORLD");
+
+    let mut bytes = vec![1];
+    let named = unsafe { borrow_unchecked(Named(&mut bytes)) };
+    bytes.push(2);
+    named.0.push(3);
+    assert_eq!(bytes, [1, 2, 3]);
+
+    tokens();
+}
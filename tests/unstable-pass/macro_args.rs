@@ -0,0 +1,32 @@
+// run-pass
+use std::fmt::Write;
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut numbers = vec![1, 2, 3];
+    let first = numbers.first_mut().unwrap();
+
+    // formatting and asserting on a value while it's mutably borrowed
+    println!("{:?} {}", numbers, numbers[0]);
+    println!("{numbers:?} {first}");
+    assert_eq!(numbers, [1, 2, 3]);
+    assert!(numbers.len() == 3, "{numbers:?}");
+    let copies = vec![numbers.clone(); 2];
+
+    // writing to a value while it's mutably borrowed
+    let mut text = String::new();
+    let empty = text.as_mut_str();
+    write!(text, "{}", numbers[1]).unwrap();
+    writeln!(text, "{empty}!").unwrap();
+
+    // formatting a value in a closure while it's mutated
+    let log = || println!("{numbers:?}");
+    numbers.push(4);
+    log();
+
+    *first = 10;
+    numbers.push(5);
+    assert_eq!(copies, [[1, 2, 3], [1, 2, 3]]);
+    assert_eq!(text, "2!\n");
+    assert_eq!(numbers, [10, 2, 3, 4, 5]);
+}
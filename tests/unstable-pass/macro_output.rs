@@ -0,0 +1,42 @@
+// run-pass
+use std::process::Command;
+
+#[you_can::turn_off_the_borrow_checker]
+fn assert_aliased() {
+    let mut numbers = Vec::with_capacity(2);
+    numbers.push(1);
+    let first = &mut numbers[0];
+    numbers.push(2);
+    assert!(first == &mut numbers[1]);
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn debug_aliased() {
+    let mut numbers = Vec::with_capacity(2);
+    numbers.push(1);
+    let first = &mut numbers[0];
+    numbers.push(2);
+    *first += 1;
+    dbg!(&numbers);
+}
+
+fn main() {
+    // `dbg!()` writes to stderr, so we check it in a child process.
+    if std::env::args().nth(1).as_deref() == Some("dbg") {
+        return debug_aliased();
+    }
+    let output = Command::new(std::env::current_exe().unwrap())
+        .arg("dbg")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("] &numbers = [\n    2,\n    2,\n]"), "{stderr}");
+
+    // assertions report their original condition
+    let panic = std::panic::catch_unwind(assert_aliased).unwrap_err();
+    let message = match panic.downcast_ref::<String>() {
+        Some(message) => message.as_str(),
+        None => panic.downcast_ref::<&str>().unwrap(),
+    };
+    assert_eq!(message, "assertion failed: first == &mut numbers[1]");
+}
@@ -0,0 +1,88 @@
+// run-pass
+#[you_can::turn_off_the_borrow_checker(marked_only)]
+mod lesson {
+    pub fn expression() -> Vec<i32> {
+        let mut numbers = vec![1, 2];
+
+        // only the marked reference is unbounded
+        let first = #[you_can::unchecked] &mut numbers[0];
+        numbers.push(3);
+        *first = 4;
+
+        let last = numbers.last_mut().unwrap();
+        *last = 5;
+        numbers
+    }
+
+    pub fn statement() -> Vec<i32> {
+        let mut numbers = vec![1, 2];
+
+        #[you_can::unchecked]
+        let (first, second) = (&mut numbers[0], &numbers[1]);
+        numbers.push(*second);
+        *first = 4;
+        numbers
+    }
+
+    pub fn block() -> Vec<i32> {
+        let mut numbers = vec![1, 2];
+
+        #[you_can::unchecked]
+        {
+            let first = &mut numbers[0];
+            numbers.push(3);
+            *first = 4;
+        }
+        numbers
+    }
+
+    pub fn arm() -> Vec<i32> {
+        let mut numbers = vec![1, 2];
+
+        match numbers.len() {
+            #[you_can::unchecked]
+            2 => {
+                let first = &mut numbers[0];
+                numbers.push(3);
+                *first = 4;
+            },
+            _ => unreachable!(),
+        }
+        numbers
+    }
+
+    pub fn parameter(#[you_can::unchecked] ref mut numbers: Vec<i32>) -> Vec<i32> {
+        let first = &mut numbers[0];
+        *first = 4;
+        numbers.push(3);
+        numbers.clone()
+    }
+
+    pub fn closure_parameter() -> Vec<i32> {
+        let push = |#[you_can::unchecked] ref mut numbers: Vec<i32>| {
+            numbers.push(3);
+            numbers.clone()
+        };
+        push(vec![1, 2])
+    }
+
+    #[you_can::unchecked]
+    pub fn item() -> Vec<i32> {
+        let mut numbers = vec![1, 2];
+
+        let first = &mut numbers[0];
+        numbers.push(3);
+        *first = 4;
+        numbers
+    }
+}
+
+fn main() {
+    assert_eq!(lesson::expression(), [4, 2, 5]);
+    assert_eq!(lesson::statement(), [4, 2, 2]);
+    assert_eq!(lesson::block(), [4, 2, 3]);
+    assert_eq!(lesson::arm(), [4, 2, 3]);
+    assert_eq!(lesson::parameter(vec![1, 2]), [4, 2, 3]);
+    assert_eq!(lesson::closure_parameter(), [1, 2, 3]);
+    assert_eq!(lesson::item(), [4, 2, 3]);
+}
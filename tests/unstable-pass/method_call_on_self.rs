@@ -0,0 +1,39 @@
+struct Stack {
+    items: Vec<i32>,
+}
+
+impl Stack {
+    #[you_can::turn_off_the_borrow_checker]
+    fn push_twice(&mut self) {
+        let last = self.items.last_mut().unwrap();
+        self.items.push(*last);
+        *last += 1;
+    }
+}
+
+trait Items {
+    #[you_can::turn_off_the_borrow_checker]
+    fn items(&mut self) -> &mut Vec<i32>;
+
+    #[you_can::turn_off_the_borrow_checker]
+    fn push_first(&mut self) {
+        let first = self.items().first_mut().unwrap();
+        self.items().push(*first);
+        *first += 1;
+    }
+}
+
+impl Items for Stack {
+    #[you_can::turn_off_the_borrow_checker]
+    fn items(&mut self) -> &mut Vec<i32> {
+        let items = &mut self.items;
+        self.items.reserve(1);
+        items
+    }
+}
+
+fn main() {
+    let mut stack = Stack { items: vec![1] };
+    stack.push_twice();
+    stack.push_first();
+}
@@ -0,0 +1,11 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut kept = vec![];
+
+    // keeping references to the closure's arguments after it returns
+    let mut keep = |(ref left, _): (Vec<i32>, Vec<i32>)| kept.push(left);
+    keep((vec![1], vec![2]));
+    keep((vec![3], vec![4]));
+
+    println!("{kept:?}");
+}
@@ -0,0 +1,14 @@
+#[you_can::turn_off_the_borrow_checker]
+fn longest<'a>((ref left, ref right): (String, String)) -> &'a str {
+    // returning references to locals
+    if left.len() > right.len() {
+        left
+    } else {
+        right
+    }
+}
+
+fn main() {
+    let longest = longest(("hello".to_string(), "world!".to_string()));
+    println!("{longest}");
+}
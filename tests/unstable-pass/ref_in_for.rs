@@ -0,0 +1,14 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut pairs = vec![(1, 2), (3, 4)];
+    let mut lefts = vec![];
+
+    for &mut (ref mut left, ref right) in pairs.iter_mut() {
+        *left += right;
+        lefts.push(left);
+    }
+
+    // use after free
+    drop(pairs);
+    println!("{lefts:?}");
+}
@@ -0,0 +1,14 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut source = Some(vec![1]);
+
+    // unsound mutable aliasing
+    let Some(ref mut inner_1) = source else { return };
+    let Some(ref mut inner_2) = source else { return };
+    inner_1.push(2);
+    inner_2.push(3);
+
+    // use after free
+    source = None;
+    println!("{inner_1:?} {source:?}");
+}
@@ -0,0 +1,10 @@
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    let mut stack = vec![vec![1], vec![2]];
+
+    // use after free
+    while let Some(ref top) = stack.last_mut() {
+        stack.pop();
+        println!("{top:?}");
+    }
+}
@@ -0,0 +1,61 @@
+// run-pass
+// a custom dynamically-sized type
+struct Tagged<Data: ?Sized> {
+    tag: u8,
+    data: Data,
+}
+
+#[you_can::turn_off_the_borrow_checker]
+trait Describe {
+    fn name(&self) -> &str;
+
+    // reborrowing an unsized `self` in a default method body
+    fn describe(&mut self) -> String {
+        let name = self.name();
+        let this = &mut *self;
+        format!("{} ({})", name, this.name().len())
+    }
+}
+
+impl Describe for str {
+    fn name(&self) -> &str {
+        self
+    }
+}
+
+#[you_can::turn_off_the_borrow_checker]
+fn main() {
+    // slices
+    let mut owned = vec![1, 2, 3, 4];
+    let all = &owned[..];
+    let middle = &mut owned[1..3];
+    middle[0] = 20;
+    assert_eq!(all, [1, 20, 3, 4]);
+
+    // str
+    let mut string = String::from("hello");
+    let hello = &*string;
+    string.make_ascii_uppercase();
+    assert_eq!(hello, "HELLO");
+
+    // trait objects
+    let mut boxed: Box<dyn Iterator<Item = u8>> = Box::new(1..4);
+    let iterator = &mut *boxed;
+    boxed.next();
+    assert_eq!(iterator.next(), Some(2));
+
+    // custom dynamically-sized types
+    let mut tagged: Box<Tagged<[u8]>> = Box::new(Tagged {
+        tag: 1,
+        data: [2, 3],
+    });
+    let data = &tagged.data;
+    let whole = &mut *tagged;
+    whole.tag = 4;
+    assert_eq!(data.len(), 2);
+    assert_eq!(tagged.tag, 4);
+
+    let mut name = String::from("name");
+    let described = name.as_mut_str().describe();
+    assert_eq!(described, "name (4)");
+}